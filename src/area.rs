@@ -14,7 +14,10 @@
 
 //! A rectangular region in the tree.
 
-use crate::point;
+use crate::{
+    error::Error,
+    point,
+};
 use derive_builder::Builder;
 use num::PrimInt;
 #[cfg(feature = "serde")]
@@ -24,6 +27,10 @@ use serde::{
 };
 use std::{
     cmp::PartialOrd,
+    convert::{
+        TryFrom,
+        TryInto,
+    },
     default::Default,
     fmt::Debug,
 };
@@ -34,8 +41,23 @@ use std::{
 ///
 /// **NB:**
 ///   - The top-left anchor can be any valid `(U, U)` coordinate, positive or negative, in any
-///   quadrant.
+///     quadrant.
 ///   - The width and height must both be positive and nonzero.
+///
+/// Regions are most easily built with [`Area::new()`], or with `TryFrom<(P, (U, U))>`:
+///
+/// ```
+/// use quadtree_rs::{area::Area, Error};
+/// use std::convert::TryFrom;
+///
+/// let a = Area::<u8>::new((1, 2), (3, 4)).unwrap();
+/// assert_eq!(Area::try_from(((1, 2), (3, 4))), Ok(a));
+///
+/// assert_eq!(Area::<u8>::new((1, 2), (0, 4)), Err(Error::InvalidDimensions));
+/// assert_eq!(Area::<u8>::new((250, 2), (10, 4)), Err(Error::Overflow));
+/// ```
+///
+/// [`Area::new()`]: #method.new
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Builder)]
 #[builder(build_fn(validate = "Self::validate"))]
@@ -107,6 +129,25 @@ impl<U> Area<U>
 where
     U: PrimInt + Default,
 {
+    /// Creates a new region from its top-left anchor and its `(width, height)` dimensions.
+    ///
+    /// Returns [`Error::InvalidDimensions`] if either dimension is nonpositive, or
+    /// [`Error::Overflow`] if the bottom or right edge of the region can't be represented by `U`.
+    ///
+    /// [`Error::InvalidDimensions`]: ../error/enum.Error.html#variant.InvalidDimensions
+    /// [`Error::Overflow`]: ../error/enum.Error.html#variant.Overflow
+    pub fn new(anchor: impl Into<point::Point<U>>, dimensions: (U, U)) -> Result<Self, Error> {
+        let anchor = anchor.into();
+        let (w, h) = dimensions;
+        if w <= U::zero() || h <= U::zero() {
+            return Err(Error::InvalidDimensions);
+        }
+        if anchor.x().checked_add(&w).is_none() || anchor.y().checked_add(&h).is_none() {
+            return Err(Error::Overflow);
+        }
+        Ok(Self { anchor, dimensions })
+    }
+
     /// The top-left coordinate (anchor) of the region.
     pub fn anchor(&self) -> point::Point<U> {
         self.anchor
@@ -128,8 +169,13 @@ where
     }
 
     /// The coordinate of the bottom edge of the region.
+    ///
+    /// Saturates at `U::max_value()` for regions which were built unchecked (i.e. with
+    /// [`AreaBuilder`]) and extend past it.
+    ///
+    /// [`AreaBuilder`]: struct.AreaBuilder.html
     pub fn bottom_edge(&self) -> U {
-        self.anchor().y() + self.height()
+        self.anchor().y().saturating_add(self.height())
     }
 
     /// The coordinate of the left edge of the region.
@@ -138,13 +184,23 @@ where
     }
 
     /// The coordinate of the right edge of the region.
+    ///
+    /// Saturates at `U::max_value()` for regions which were built unchecked (i.e. with
+    /// [`AreaBuilder`]) and extend past it.
+    ///
+    /// [`AreaBuilder`]: struct.AreaBuilder.html
     pub fn right_edge(&self) -> U {
-        self.anchor().x() + self.width()
+        self.anchor().x().saturating_add(self.width())
     }
 
     /// Whether or not an area intersects another area.
-    pub fn intersects(self, other: impl Into<Self>) -> bool {
-        let other = other.into();
+    ///
+    /// If `other` isn't a valid region, it intersects nothing.
+    pub fn intersects(self, other: impl TryInto<Self>) -> bool {
+        let other = match other.try_into() {
+            Ok(other) => other,
+            Err(_) => return false,
+        };
         self.left_edge() < other.right_edge()
             && self.right_edge() > other.left_edge()
             && self.top_edge() < other.bottom_edge()
@@ -152,8 +208,13 @@ where
     }
 
     /// Whether or not an area wholly contains another area.
-    pub fn contains(self, other: impl Into<Self>) -> bool {
-        let other = other.into();
+    ///
+    /// If `other` isn't a valid region, it isn't contained by anything.
+    pub fn contains(self, other: impl TryInto<Self>) -> bool {
+        let other = match other.try_into() {
+            Ok(other) => other,
+            Err(_) => return false,
+        };
         other.right_edge() <= self.right_edge()
             && other.left_edge() >= self.left_edge()
             && other.top_edge() >= self.top_edge()
@@ -162,13 +223,11 @@ where
 
     /// Whether or not an area contains a point.
     pub fn contains_pt(self, pt: impl Into<point::Point<U>>) -> bool {
-        self.contains(
-            AreaBuilder::default()
-                .anchor(pt)
-                .dimensions((U::one(), U::one()))
-                .build()
-                .expect("Unexpected error in Area::contains_pt."),
-        )
+        let pt = pt.into();
+        pt.x() >= self.left_edge()
+            && pt.x() < self.right_edge()
+            && pt.y() >= self.top_edge()
+            && pt.y() < self.bottom_edge()
     }
 
     // NB: The center point is an integer and thus rounded, i.e. a 2x2 region at (0,0) has a center
@@ -185,23 +244,58 @@ where
         self.dimensions
     }
 
+    // Whether this region, a node's, contains the top-left corner of the overlap of @a and @b.
+    // A handle is held by a set of nodes which exactly cover its region, so of those nodes only
+    // one passes this test for any @b overlapping the handle's region @a. Reporting an entry
+    // only from that node reports it once.
+    pub(crate) fn holds_overlap_corner(&self, a: Self, b: Self) -> bool {
+        self.contains_pt(point::Point {
+            x: a.left_edge().max(b.left_edge()),
+            y: a.top_edge().max(b.top_edge()),
+        })
+    }
+
+    // Fails if the shifted region can't be represented by U.
+    pub(crate) fn translated(&self, dx: U, dy: U) -> Result<Self, Error> {
+        let anchor = point::Point {
+            x: self.anchor.x().checked_add(&dx).ok_or(Error::Overflow)?,
+            y: self.anchor.y().checked_add(&dy).ok_or(Error::Overflow)?,
+        };
+        Self::new(anchor, self.dimensions)
+    }
+
+    // Scales the anchor and dimensions by @factor. Fails if the scaled region can't be
+    // represented by U, or if @factor isn't positive.
+    pub(crate) fn scaled(&self, factor: U) -> Result<Self, Error> {
+        let mul = |n: U| n.checked_mul(&factor).ok_or(Error::Overflow);
+        let anchor = point::Point {
+            x: mul(self.anchor.x())?,
+            y: mul(self.anchor.y())?,
+        };
+        Self::new(anchor, (mul(self.width())?, mul(self.height())?))
+    }
+
+    // Callers must ensure that the dimensions are positive and the edges fit in U, i.e. when
+    // carving up a region which is already known to be valid.
+    pub(crate) fn new_unchecked(anchor: point::Point<U>, dimensions: (U, U)) -> Self {
+        Self { anchor, dimensions }
+    }
+
     // Strongly-typed alias for U::one() + U::One()
     fn two() -> U {
         U::one() + U::one()
     }
 }
 
-impl<P, U> From<(P, (U, U))> for Area<U>
+impl<P, U> TryFrom<(P, (U, U))> for Area<U>
 where
     P: Into<point::Point<U>>,
     U: PrimInt + Default + PartialOrd,
 {
-    fn from((anchor, dimensions): (P, (U, U))) -> Self {
-        AreaBuilder::default()
-            .anchor(anchor)
-            .dimensions(dimensions)
-            .build()
-            .unwrap()
+    type Error = Error;
+
+    fn try_from((anchor, dimensions): (P, (U, U))) -> Result<Self, Error> {
+        Self::new(anchor, dimensions)
     }
 }
 
@@ -211,6 +305,9 @@ where
     U: PrimInt + Default + PartialOrd,
 {
     fn from(anchor: P) -> Self {
-        AreaBuilder::default().anchor(anchor).build().unwrap()
+        Self {
+            anchor: anchor.into(),
+            dimensions: (U::one(), U::one()),
+        }
     }
 }
@@ -0,0 +1,559 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A compact, versioned binary format for saving and loading a [`Quadtree`].
+//!
+//! The format describes the tree by its root geometry and its entries, not by its internal node
+//! layout, so files stay readable across changes to the tree's internals. On load, the tree is
+//! rebuilt from the entry table.
+//!
+//! # Layout
+//!
+//! All integers are little-endian. Coordinates are written in the width of the coordinate type
+//! `U` (i.e. two bytes for `u16`), and values are written with their [`Codec`] implementation.
+//!
+//! | Section        | Field                 | Encoding                                   |
+//! |----------------|-----------------------|--------------------------------------------|
+//! | Header         | magic                 | the four bytes `QTRS`                      |
+//! |                | version               | `u16`, currently `1`                       |
+//! |                | flags                 | `u16`; bit 0 is set if a tree section follows |
+//! |                | coordinate width      | `u8`, in bytes                             |
+//! |                | coordinate signedness | `u8`, `1` if signed                        |
+//! |                | depth                 | `u32`                                      |
+//! |                | anchor                | two coordinates, `x` then `y`              |
+//! |                | handle counter        | `u64`, the next handle to be handed out    |
+//! |                | entry count           | `u64`                                      |
+//! | Entry table    | handle                | `u64`, in increasing order                 |
+//! |                | region                | four coordinates: `x`, `y`, width, height  |
+//! |                | value                 | `u32` length, then that many bytes         |
+//! | Tree (optional)| one record per node   | `u32` handle count, that many `u64` handles, then `u8` `1` if the node has subquadrants |
+//!
+//! Tree records are written depth-first, with subquadrants in the order northeast, northwest,
+//! southeast, southwest. The tree section is redundant: readers always rebuild the tree from the
+//! entry table, and reject the file if a tree section is present and disagrees with it.
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`Codec`]: trait.Codec.html
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    types::StoreType,
+};
+use num::PrimInt;
+use std::{
+    convert::{
+        TryFrom,
+        TryInto,
+    },
+    default::Default,
+    fmt,
+    mem,
+};
+
+const MAGIC: &[u8; 4] = b"QTRS";
+/// The version of the format written by this crate.
+pub const VERSION: u16 = 1;
+const FLAG_TREE: u16 = 1;
+
+/// How values are written to and read from the [binary format].
+///
+/// Implemented for the primitive numeric types, `bool`, `char`, `()`, `String`, and `Vec<u8>`.
+///
+/// [binary format]: index.html
+pub trait Codec: Sized {
+    /// Appends the encoding of this value to `out`.
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from exactly the bytes written by [`.encode()`], or returns `None` if
+    /// they're invalid.
+    ///
+    /// [`.encode()`]: #tymethod.encode
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_codec_for_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl Codec for $t {
+                fn encode(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_le_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    Some(Self::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_codec_for_numbers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl Codec for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Codec for char {
+    fn encode(&self, out: &mut Vec<u8>) {
+        u32::from(*self).encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        char::from_u32(u32::decode(bytes)?)
+    }
+}
+
+impl Codec for () {
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.is_empty().then_some(())
+    }
+}
+
+impl Codec for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+impl Codec for Vec<u8> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+/// The error returned when bytes can't be read as a [`Quadtree`].
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DecodeError {
+    /// The input ended partway through.
+    Truncated,
+    /// The input doesn't start with the format's magic bytes.
+    BadMagic,
+    /// The input was written in a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The header sets flags this version of the format doesn't know about.
+    UnsupportedFlags(u16),
+    /// The input's coordinates are of a different width or signedness than `U`.
+    CoordinateMismatch,
+    /// The root region can't be represented by `U`.
+    InvalidRoot,
+    /// The entry with this handle has an invalid region, or one which doesn't fit in the root, or
+    /// a handle no less than the handle counter.
+    InvalidEntry(u64),
+    /// Handles in the entry table are repeated or out of order.
+    DuplicateHandle(u64),
+    /// The value of the entry with this handle couldn't be decoded.
+    InvalidValue(u64),
+    /// The tree section disagrees with the entry table.
+    TreeMismatch,
+    /// There are bytes left over after the last section.
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "input ended unexpectedly"),
+            DecodeError::BadMagic => write!(f, "input is not a serialized quadtree"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            DecodeError::UnsupportedFlags(flags) => write!(f, "unsupported flags {:#06x}", flags),
+            DecodeError::CoordinateMismatch => {
+                write!(f, "coordinates don't match the coordinate type")
+            }
+            DecodeError::InvalidRoot => write!(f, "root region can't be represented"),
+            DecodeError::InvalidEntry(handle) => write!(f, "invalid entry with handle {}", handle),
+            DecodeError::DuplicateHandle(handle) => {
+                write!(f, "handle {} is repeated or out of order", handle)
+            }
+            DecodeError::InvalidValue(handle) => {
+                write!(f, "invalid value for entry with handle {}", handle)
+            }
+            DecodeError::TreeMismatch => write!(f, "tree section disagrees with entry table"),
+            DecodeError::TrailingBytes => write!(f, "unexpected bytes after the last section"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// The error returned when a [`Quadtree`] can't be written in the [binary format].
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [binary format]: index.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The encoded value of the entry with this handle is too long for its `u32` length, i.e.
+    /// 4GiB or longer.
+    ValueTooLong(u64),
+    /// A node holds too many handles for the tree section's `u32` handle count.
+    TooManyHandles,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodeError::ValueTooLong(handle) => {
+                write!(f, "value of entry with handle {} is too long", handle)
+            }
+            EncodeError::TooManyHandles => write!(f, "a node holds too many handles"),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Codec,
+{
+    /// Writes this quadtree in the [binary format], without a tree section.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, String>::new(4);
+    /// let handle = qt.insert(((1, 2), (3, 4)), "foo".to_string()).unwrap();
+    ///
+    /// let bytes = qt.to_bytes().unwrap();
+    /// let copy = Quadtree::<u32, String>::from_bytes(&bytes).unwrap();
+    ///
+    /// assert_eq!(copy.get(handle).unwrap().value_ref(), "foo");
+    /// assert_eq!(copy.query((2, 3)).count(), 1);
+    /// ```
+    ///
+    /// Fails with an [`EncodeError`] if a value is too long for the format.
+    ///
+    /// [binary format]: binary/index.html
+    /// [`EncodeError`]: binary/enum.EncodeError.html
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EncodeError> {
+        self.write_bytes(false)
+    }
+
+    /// Writes this quadtree in the [binary format], with a tree section describing its nodes.
+    ///
+    /// Fails with an [`EncodeError`] if a value, or a node's handle count, is too long for the
+    /// format.
+    ///
+    /// [binary format]: binary/index.html
+    /// [`EncodeError`]: binary/enum.EncodeError.html
+    pub fn to_bytes_with_tree(&self) -> Result<Vec<u8>, EncodeError> {
+        self.write_bytes(true)
+    }
+
+    /// Reads a quadtree from the [binary format], and rebuilds its tree from the entry table.
+    ///
+    /// Handles are preserved. The input is fully validated, so a malformed file returns a
+    /// [`DecodeError`] rather than an inconsistent tree.
+    /// ```
+    /// use quadtree_rs::{binary::DecodeError, Quadtree};
+    ///
+    /// let qt = Quadtree::<u8, u8>::new(2);
+    /// let bytes = qt.to_bytes().unwrap();
+    ///
+    /// // The coordinate type must match.
+    /// assert_eq!(
+    ///     Quadtree::<u16, u8>::from_bytes(&bytes),
+    ///     Err(DecodeError::CoordinateMismatch)
+    /// );
+    /// assert_eq!(
+    ///     Quadtree::<u8, u8>::from_bytes(&bytes[..8]),
+    ///     Err(DecodeError::Truncated)
+    /// );
+    /// ```
+    ///
+    /// [binary format]: binary/index.html
+    /// [`DecodeError`]: binary/enum.DecodeError.html
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader { bytes };
+
+        if r.take(4)? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = r.u16()?;
+        if version > VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let flags = r.u16()?;
+        if flags & !FLAG_TREE != 0 {
+            return Err(DecodeError::UnsupportedFlags(flags));
+        }
+        if (r.u8()?, r.u8()?) != coordinate_format::<U>() {
+            return Err(DecodeError::CoordinateMismatch);
+        }
+        let depth = r.u32()? as usize;
+        let anchor = Point {
+            x: r.coord()?,
+            y: r.coord()?,
+        };
+        let handle_counter = r.u64()?;
+        let entry_count = r.u64()?;
+
+        let mut inner = QTInner::try_new(anchor, depth).map_err(|_| DecodeError::InvalidRoot)?;
+        let mut store: StoreType<U, V> = StoreType::new();
+        let mut last_handle = None;
+        for _ in 0..entry_count {
+            let handle = r.u64()?;
+            if last_handle.is_some_and(|last| handle <= last) {
+                return Err(DecodeError::DuplicateHandle(handle));
+            }
+            last_handle = Some(handle);
+
+            let entry_anchor = Point {
+                x: r.coord()?,
+                y: r.coord()?,
+            };
+            let dimensions = (r.coord()?, r.coord()?);
+            let region = Area::new(entry_anchor, dimensions)
+                .ok()
+                .filter(|region| inner.region().contains(*region) && handle < handle_counter)
+                .ok_or(DecodeError::InvalidEntry(handle))?;
+
+            let len = r.u32()? as usize;
+            let value = V::decode(r.take(len)?).ok_or(DecodeError::InvalidValue(handle))?;
+            inner.insert_entry(Entry::new((region, value), handle), &mut store);
+        }
+        inner.set_handle_counter(handle_counter);
+
+        if flags & FLAG_TREE != 0 {
+            let mut written = vec![];
+            read_tree(&mut r, inner.region(), depth, &mut written)?;
+            let mut rebuilt = vec![];
+            placements(&inner, &mut rebuilt);
+            written.sort_unstable();
+            rebuilt.sort_unstable();
+            if written != rebuilt {
+                return Err(DecodeError::TreeMismatch);
+            }
+        }
+        if !r.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(Quadtree::from_parts(inner, store))
+    }
+
+    fn write_bytes(&self, with_tree: bool) -> Result<Vec<u8>, EncodeError> {
+        let inner = self.inner();
+        let mut out = vec![];
+
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        let flags = if with_tree { FLAG_TREE } else { 0 };
+        out.extend_from_slice(&flags.to_le_bytes());
+        let (width, signed) = coordinate_format::<U>();
+        out.extend_from_slice(&[width, signed]);
+        out.extend_from_slice(&(inner.depth() as u32).to_le_bytes());
+        write_coord(&mut out, inner.region().anchor().x());
+        write_coord(&mut out, inner.region().anchor().y());
+        out.extend_from_slice(&inner.handle_counter().to_le_bytes());
+        out.extend_from_slice(&(self.len() as u64).to_le_bytes());
+
+        let mut value = vec![];
+        for entry in self.entries_by_handle() {
+            out.extend_from_slice(&entry.handle().to_le_bytes());
+            write_coord(&mut out, entry.anchor().x());
+            write_coord(&mut out, entry.anchor().y());
+            write_coord(&mut out, entry.width());
+            write_coord(&mut out, entry.height());
+            value.clear();
+            entry.value_ref().encode(&mut value);
+            let len = u32::try_from(value.len())
+                .map_err(|_| EncodeError::ValueTooLong(entry.handle()))?;
+            out.extend_from_slice(&len.to_le_bytes());
+            out.extend_from_slice(&value);
+        }
+
+        if with_tree {
+            write_tree(&mut out, inner)?;
+        }
+        Ok(out)
+    }
+}
+
+// The coordinate width in bytes, and 1 if the coordinate type is signed.
+pub(crate) fn coordinate_format<U: PrimInt>() -> (u8, u8) {
+    (
+        mem::size_of::<U>() as u8,
+        u8::from(U::min_value() < U::zero()),
+    )
+}
+
+pub(crate) fn write_coord<U: PrimInt>(out: &mut Vec<u8>, n: U) {
+    let width = mem::size_of::<U>();
+    let bytes = if U::min_value() < U::zero() {
+        n.to_i128()
+            .expect("Primitive integers fit in 128 bits.")
+            .to_le_bytes()
+    } else {
+        n.to_u128()
+            .expect("Primitive integers fit in 128 bits.")
+            .to_le_bytes()
+    };
+    out.extend_from_slice(&bytes[..width]);
+}
+
+fn write_tree<U>(out: &mut Vec<u8>, qt: &QTInner<U>) -> Result<(), EncodeError>
+where
+    U: PrimInt + Default,
+{
+    let count = u32::try_from(qt.handles().len()).map_err(|_| EncodeError::TooManyHandles)?;
+    out.extend_from_slice(&count.to_le_bytes());
+    for handle in qt.handles() {
+        out.extend_from_slice(&handle.to_le_bytes());
+    }
+    match qt.subquadrants() {
+        Some(sqs) => {
+            out.push(1);
+            for sq in sqs.iter() {
+                write_tree(out, sq)?;
+            }
+        }
+        None => out.push(0),
+    }
+    Ok(())
+}
+
+// Reads the tree section rooted at a node with @region and @depth, and collects every
+// (depth, x, y, handle) placement in it.
+fn read_tree<U>(
+    r: &mut Reader,
+    region: Area<U>,
+    depth: usize,
+    placements: &mut Vec<(usize, U, U, u64)>,
+) -> Result<(), DecodeError>
+where
+    U: PrimInt + Default,
+{
+    let count = r.u32()?;
+    for _ in 0..count {
+        placements.push((depth, region.anchor().x(), region.anchor().y(), r.u64()?));
+    }
+    match r.u8()? {
+        0 => Ok(()),
+        1 if depth > 0 => {
+            let two = U::one() + U::one();
+            let (w, h) = (region.width() / two, region.height() / two);
+            let (x, y) = (region.anchor().x(), region.anchor().y());
+            // [ne, nw, se, sw], as laid out by QTInner.
+            for (x, y) in [(x + w, y), (x, y), (x + w, y + h), (x, y + h)] {
+                read_tree(
+                    r,
+                    Area::new_unchecked((x, y).into(), (w, h)),
+                    depth - 1,
+                    placements,
+                )?;
+            }
+            Ok(())
+        }
+        _ => Err(DecodeError::TreeMismatch),
+    }
+}
+
+fn placements<U>(qt: &QTInner<U>, out: &mut Vec<(usize, U, U, u64)>)
+where
+    U: PrimInt + Default,
+{
+    let anchor = qt.region().anchor();
+    for handle in qt.handles() {
+        out.push((qt.depth(), anchor.x(), anchor.y(), *handle));
+    }
+    if let Some(sqs) = qt.subquadrants() {
+        for sq in sqs.iter() {
+            placements(sq, out);
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        u16::decode(self.take(2)?).ok_or(DecodeError::Truncated)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        u32::decode(self.take(4)?).ok_or(DecodeError::Truncated)
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        u64::decode(self.take(8)?).ok_or(DecodeError::Truncated)
+    }
+
+    fn coord<U: PrimInt>(&mut self) -> Result<U, DecodeError> {
+        read_coord(self.take(mem::size_of::<U>())?).ok_or(DecodeError::CoordinateMismatch)
+    }
+}
+
+// Reads a coordinate from exactly the width of U, sign-extending it if U is signed.
+pub(crate) fn read_coord<U: PrimInt>(bytes: &[u8]) -> Option<U> {
+    let width = mem::size_of::<U>();
+    if bytes.len() != width {
+        return None;
+    }
+    let signed = U::min_value() < U::zero();
+    let fill = if signed && bytes[width - 1] & 0x80 != 0 {
+        0xff
+    } else {
+        0
+    };
+    let mut buf = [fill; 16];
+    buf[..width].copy_from_slice(bytes);
+    if signed {
+        U::from(i128::from_le_bytes(buf))
+    } else {
+        U::from(u128::from_le_bytes(buf))
+    }
+}
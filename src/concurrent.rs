@@ -0,0 +1,431 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A quadtree which can be shared between threads, and written to from several at once.
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    types::StoreType,
+};
+use num::PrimInt;
+use std::{
+    convert::TryInto,
+    default::Default,
+    fmt::Debug,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        PoisonError,
+        RwLock,
+        RwLockReadGuard,
+        RwLockWriteGuard,
+    },
+};
+
+/// A concurrent variant of [`Quadtree`], for sharing between many threads which query it while
+/// a few insert and delete.
+///
+/// The tree is split into shards, each behind its own lock: one for each of the four top-level
+/// subquadrants, holding the entries which fit inside it, and one for the entries which span
+/// more than one. Every method takes `&self`. A write locks only the shard its entry belongs
+/// to, so writes to different parts of the tree don't wait on each other, and a query
+/// read-locks only the shards its region overlaps.
+///
+/// Entries can't be borrowed out from behind a lock, so [`.get()`], [`.query()`], and
+/// [`.query_strict()`] return clones of them. [`.query_with()`] visits them in place instead.
+///
+/// Handles are unique across the whole tree, and are preserved when converting to and from a
+/// [`Quadtree`].
+/// ```
+/// use quadtree_rs::ConcurrentQuadtree;
+/// use std::thread;
+///
+/// let qt = ConcurrentQuadtree::<u32, u32>::new(4);
+///
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let qt = &qt;
+///         s.spawn(move || qt.insert((4 * i, 4 * i), i));
+///     }
+/// });
+///
+/// assert_eq!(qt.len(), 4);
+/// assert_eq!(qt.query(((0, 0), (5, 5))).len(), 2);
+/// ```
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`.get()`]: #method.get
+/// [`.query()`]: #method.query
+/// [`.query_strict()`]: #method.query_strict
+/// [`.query_with()`]: #method.query_with
+pub struct ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    region: Area<U>,
+    depth: usize,
+    // The shards for the top-level subquadrants [ne, nw, se, sw], if the tree has any, and then
+    // the shard for entries which span several of them.
+    shards: Vec<Shard<U, V>>,
+    handle_counter: AtomicU64,
+}
+
+impl<U, V> ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth. See [`Quadtree::new()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^depth` can't be represented by `U`. See [`.try_new()`] for a fallible
+    /// variant.
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    /// [`.try_new()`]: #method.try_new
+    pub fn new(depth: usize) -> Self {
+        Self::new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor. See
+    /// [`Quadtree::new_with_anchor()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the region can't be represented by `U`. See [`.try_new_with_anchor()`] for a
+    /// fallible variant.
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    /// [`.try_new_with_anchor()`]: #method.try_new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::try_new_with_anchor(anchor, depth)
+            .expect("The quadtree's region can't be represented by its coordinate type.")
+    }
+
+    /// A fallible variant of [`.new()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth` can't be represented by `U`.
+    ///
+    /// [`.new()`]: #method.new
+    /// [`Error::Overflow`]: ../error/enum.Error.html#variant.Overflow
+    pub fn try_new(depth: usize) -> Result<Self, Error> {
+        Self::try_new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// A fallible variant of [`.new_with_anchor()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth`, or the bottom or right edge of the region, can't
+    /// be represented by `U`.
+    ///
+    /// [`.new_with_anchor()`]: #method.new_with_anchor
+    /// [`Error::Overflow`]: ../error/enum.Error.html#variant.Overflow
+    pub fn try_new_with_anchor(anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        let region = QTInner::try_new(anchor, depth)?.region();
+        let mut shards = Vec::with_capacity(5);
+        if depth > 0 {
+            let half = region.width() / (U::one() + U::one());
+            for (dx, dy) in &[
+                (half, U::zero()),
+                (U::zero(), U::zero()),
+                (half, half),
+                (U::zero(), half),
+            ] {
+                let anchor = Point {
+                    x: anchor.x + *dx,
+                    y: anchor.y + *dy,
+                };
+                shards.push(Shard::try_new(anchor, depth - 1)?);
+            }
+        }
+        shards.push(Shard::try_new(anchor, depth)?);
+        Ok(Self {
+            region,
+            depth,
+            shards,
+            handle_counter: AtomicU64::new(0),
+        })
+    }
+
+    /// The top-left corner (anchor) of the region which this quadtree represents.
+    pub fn anchor(&self) -> Point<U> {
+        self.region.anchor()
+    }
+
+    /// The width of the region which this quadtree represents.
+    ///
+    /// Saturates at `usize::MAX` if the width doesn't fit in a `usize`.
+    pub fn width(&self) -> usize {
+        self.region.width().to_usize().unwrap_or(usize::MAX)
+    }
+
+    /// The height of the region which this quadtree represents.
+    ///
+    /// Saturates at `usize::MAX` if the height doesn't fit in a `usize`.
+    pub fn height(&self) -> usize {
+        self.region.height().to_usize().unwrap_or(usize::MAX)
+    }
+
+    /// The depth of the quadtree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of elements in the quadtree. Other threads may change it as soon as it's
+    /// returned.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| read(&shard.qt).len()).sum()
+    }
+
+    /// Whether or not the quadtree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether or not the region represented by this quadtree could contain the given region.
+    pub fn contains(&self, area: impl TryInto<Area<U>>) -> bool {
+        self.region.contains(area)
+    }
+
+    /// Associates the value with the given region, and returns its handle. See
+    /// [`Quadtree::insert()`].
+    ///
+    /// Locks only the shard the region belongs to.
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&self, region: impl TryInto<Area<U>>, val: V) -> Option<u64> {
+        let region = region.try_into().ok()?;
+        if !self.contains(region) {
+            return None;
+        }
+        let handle = self.handle_counter.fetch_add(1, Ordering::Relaxed);
+        write(self.shard_for(region)).insert_entry(Entry::new((region, val), handle));
+        Some(handle)
+    }
+
+    /// Deletes the region/value association with the given handle, and returns it.
+    ///
+    /// Read-locks each shard in turn to find the entry, then locks only the shard which holds
+    /// it.
+    pub fn delete_by_handle(&self, handle: u64) -> Option<Entry<U, V>> {
+        let shard = self
+            .shards
+            .iter()
+            .find(|shard| read(&shard.qt).get(handle).is_some())?;
+        // If another thread deleted the entry in the meantime, this returns None.
+        write(&shard.qt).delete_by_handle(handle)
+    }
+
+    /// Calls `f` on every region/value association which intersects the given region.
+    ///
+    /// The shards the region overlaps are read-locked together for the duration, so `f` sees a
+    /// consistent view of them. `f` must not write to this quadtree, or it may deadlock.
+    /// ```
+    /// use quadtree_rs::ConcurrentQuadtree;
+    ///
+    /// let qt = ConcurrentQuadtree::<u8, u64>::new(3);
+    /// qt.insert(((0, 0), (8, 8)), 3);
+    /// qt.insert((1, 1), 4);
+    ///
+    /// let mut sum = 0;
+    /// qt.query_with((1, 1), |e| sum += e.value_ref());
+    /// assert_eq!(sum, 7);
+    /// ```
+    pub fn query_with<F>(&self, area: impl TryInto<Area<U>>, mut f: F)
+    where
+        F: FnMut(&Entry<U, V>),
+    {
+        if let Ok(area) = area.try_into() {
+            for shard in self.read_overlapping(area) {
+                shard.query(area).for_each(&mut f);
+            }
+        }
+    }
+
+    // fn
+
+    // The shard which holds entries over @region: the top-level subquadrant which contains it,
+    // or else the last shard.
+    fn shard_for(&self, region: Area<U>) -> &RwLock<Quadtree<U, V>> {
+        let (last, quadrants) = self
+            .shards
+            .split_last()
+            .expect("There is always a last shard.");
+        &quadrants
+            .iter()
+            .find(|shard| shard.region.contains(region))
+            .unwrap_or(last)
+            .qt
+    }
+
+    // Read-locks every shard which might hold an entry intersecting @area, in order.
+    fn read_overlapping(&self, area: Area<U>) -> Vec<RwLockReadGuard<'_, Quadtree<U, V>>> {
+        let last = self.shards.len() - 1;
+        self.shards
+            .iter()
+            .enumerate()
+            .filter(|(i, shard)| *i == last || shard.region.intersects(area))
+            .map(|(_, shard)| read(&shard.qt))
+            .collect()
+    }
+}
+
+impl<U, V> ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    // pub
+
+    /// Returns a clone of the region/value association with the given handle, if there is one.
+    pub fn get(&self, handle: u64) -> Option<Entry<U, V>> {
+        self.shards
+            .iter()
+            .find_map(|shard| read(&shard.qt).get(handle).cloned())
+    }
+
+    /// Returns clones of all region/value associations which intersect the given region. See
+    /// [`Quadtree::query()`].
+    ///
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: impl TryInto<Area<U>>) -> Vec<Entry<U, V>> {
+        let mut entries = Vec::new();
+        self.query_with(area, |entry| entries.push(entry.clone()));
+        entries
+    }
+
+    /// Returns clones of all region/value associations which are contained by the given region.
+    /// See [`Quadtree::query_strict()`].
+    ///
+    /// [`Quadtree::query_strict()`]: ../struct.Quadtree.html#method.query_strict
+    pub fn query_strict(&self, area: impl TryInto<Area<U>>) -> Vec<Entry<U, V>> {
+        let mut entries = Vec::new();
+        if let Ok(area) = area.try_into() {
+            for shard in self.read_overlapping(area) {
+                entries.extend(shard.query_strict(area).cloned());
+            }
+        }
+        entries
+    }
+}
+
+impl<U, V> Debug for ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ConcurrentQuadtree")
+            .field("region", &self.region)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Converts from a [`Quadtree`] in O(n), keeping every handle.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+impl<U, V> From<Quadtree<U, V>> for ConcurrentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn from(qt: Quadtree<U, V>) -> Self {
+        let cqt = Self::new_with_anchor(qt.anchor(), qt.depth());
+        let (inner, store) = qt.into_parts();
+        for (_, entry) in store {
+            write(cqt.shard_for(entry.area())).insert_entry(entry);
+        }
+        cqt.handle_counter
+            .store(inner.handle_counter(), Ordering::Relaxed);
+        cqt
+    }
+}
+
+/// Converts back to a [`Quadtree`] in O(n), keeping every handle.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+impl<U, V> From<ConcurrentQuadtree<U, V>> for Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn from(qt: ConcurrentQuadtree<U, V>) -> Self {
+        let mut inner = QTInner::try_new(qt.region.anchor(), qt.depth)
+            .expect("The region was representable when the quadtree was made.");
+        let mut store: StoreType<U, V> = StoreType::new();
+        for shard in qt.shards {
+            let (_, entries) = shard
+                .qt
+                .into_inner()
+                .unwrap_or_else(PoisonError::into_inner)
+                .into_parts();
+            for (_, entry) in entries {
+                inner.insert_entry(entry, &mut store);
+            }
+        }
+        inner.set_handle_counter(qt.handle_counter.into_inner());
+        Quadtree::from_parts(inner, store)
+    }
+}
+
+// A shard and the region it covers, which is fixed, so needn't be locked to read.
+struct Shard<U, V>
+where
+    U: PrimInt + Default,
+{
+    region: Area<U>,
+    qt: RwLock<Quadtree<U, V>>,
+}
+
+impl<U, V> Shard<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn try_new(anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        let qt = Quadtree::try_new_with_anchor(anchor, depth)?;
+        Ok(Self {
+            region: qt.inner().region(),
+            qt: RwLock::new(qt),
+        })
+    }
+}
+
+// Write locks are only held across calls into a shard's Quadtree, which don't panic, and read
+// locks aren't poisoned by a panic in a .query_with() callback. So a lock is never left poisoned
+// with its shard inconsistent, and poisoning is ignored.
+fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(PoisonError::into_inner)
+}
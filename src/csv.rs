@@ -0,0 +1,476 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Loading and dumping regions as CSV rows of `x,y,width,height,value`.
+//!
+//! Which column holds which field, whether there's a header row, and the delimiter are set by
+//! a [`Format`]. Values are parsed from, and written as, text by closures, so there's no need
+//! for the value type to implement any particular trait.
+//!
+//! Fields may be quoted as in [RFC 4180](https://datatracker.ietf.org/doc/html/rfc4180): a
+//! quoted field may hold delimiters and line breaks, and `""` within it is a literal `"`.
+//! Blank lines are skipped.
+//! ```
+//! use quadtree_rs::{csv::Format, Quadtree};
+//!
+//! let input = "\
+//! 0,0,2,2,lake
+//! 5,6,1,1,well
+//! 9,9,9,9,too big
+//! ";
+//!
+//! let mut qt = Quadtree::<u32, String>::new(3);
+//! let results: Vec<_> = qt
+//!     .read_csv(input.as_bytes(), &Format::default(), |s| {
+//!         Ok::<_, ()>(s.to_string())
+//!     })
+//!     .collect();
+//! assert!(results[0].is_ok() && results[1].is_ok());
+//! assert!(results[2].is_err());
+//! assert_eq!(qt.len(), 2);
+//!
+//! let mut output = Vec::new();
+//! qt.write_csv(&mut output, &Format::default(), |v| v.clone()).unwrap();
+//! assert_eq!(output, b"0,0,2,2,lake\n5,6,1,1,well\n");
+//! ```
+//!
+//! [`Format`]: struct.Format.html
+
+use crate::{
+    entry::Entry,
+    error::Error,
+    quadtree::Quadtree,
+};
+use derive_builder::Builder;
+use num::PrimInt;
+use std::{
+    default::Default,
+    fmt,
+    io::{
+        self,
+        BufRead,
+        Write,
+    },
+    str::FromStr,
+};
+
+/// The layout of CSV rows. Build one with a [`FormatBuilder`], or use the default.
+///
+/// By default, the columns are `x,y,width,height,value` in that order, there's no header row,
+/// and the delimiter is `,`.
+/// ```
+/// use quadtree_rs::csv::{Format, FormatBuilder};
+///
+/// // value;x;y;width;height, with a header row.
+/// let format = FormatBuilder::default()
+///     .value(0)
+///     .x(1)
+///     .y(2)
+///     .width(3)
+///     .height(4)
+///     .header(true)
+///     .delimiter(';')
+///     .build()
+///     .unwrap();
+/// assert_ne!(format, Format::default());
+///
+/// // Two fields can't share a column.
+/// assert!(FormatBuilder::default().x(1).build().is_err());
+/// ```
+///
+/// [`FormatBuilder`]: struct.FormatBuilder.html
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Builder)]
+#[builder(build_fn(validate = "Self::validate"))]
+pub struct Format {
+    /// The column of the anchor's `x` coordinate.
+    #[builder(default = "0")]
+    x: usize,
+    /// The column of the anchor's `y` coordinate.
+    #[builder(default = "1")]
+    y: usize,
+    /// The column of the width.
+    #[builder(default = "2")]
+    width: usize,
+    /// The column of the height.
+    #[builder(default = "3")]
+    height: usize,
+    /// The column of the value.
+    #[builder(default = "4")]
+    value: usize,
+    /// Whether the first row is a header, which is skipped on reading and written on writing.
+    #[builder(default = "false")]
+    header: bool,
+    /// The field delimiter.
+    #[builder(default = "','")]
+    delimiter: char,
+}
+
+impl FormatBuilder {
+    fn validate(&self) -> Result<(), String> {
+        let format = Format {
+            x: self.x.unwrap_or(0),
+            y: self.y.unwrap_or(1),
+            width: self.width.unwrap_or(2),
+            height: self.height.unwrap_or(3),
+            value: self.value.unwrap_or(4),
+            header: false,
+            delimiter: self.delimiter.unwrap_or(','),
+        };
+        let columns = format.columns();
+        if (1..columns.len()).any(|i| columns[..i].contains(&columns[i])) {
+            return Err("Two fields may not share a column.".to_string());
+        }
+        if ['"', '\r', '\n'].contains(&format.delimiter) {
+            return Err("The delimiter may not be a quote or a line break.".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        FormatBuilder::default().build().unwrap()
+    }
+}
+
+impl Format {
+    // The columns of x, y, width, height, and value, in that order.
+    fn columns(&self) -> [usize; 5] {
+        [self.x, self.y, self.width, self.height, self.value]
+    }
+}
+
+/// The error returned for a CSV row which couldn't be loaded into a [`Quadtree`].
+///
+/// Lines count from one, and columns from zero. A row's line is the line it starts on.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CsvError<E> {
+    /// The input couldn't be read. No further rows are read.
+    Io(io::Error),
+    /// The row at this line ends in an unterminated quoted field. It's the last row.
+    UnterminatedQuote(usize),
+    /// The row at this line has no field in this column.
+    MissingColumn(usize, usize),
+    /// The field in this column of the row at this line isn't a valid coordinate.
+    InvalidNumber(usize, usize),
+    /// The value of the row at this line couldn't be parsed.
+    InvalidValue(usize, E),
+    /// The region of the row at this line can't be inserted into the quadtree.
+    Insert(usize, Error),
+}
+
+impl<E> fmt::Display for CsvError<E>
+where
+    E: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CsvError::Io(e) => write!(f, "couldn't read CSV: {}", e),
+            CsvError::UnterminatedQuote(l) => write!(f, "line {}: unterminated quoted field", l),
+            CsvError::MissingColumn(l, c) => write!(f, "line {}: missing column {}", l, c),
+            CsvError::InvalidNumber(l, c) => {
+                write!(f, "line {}: column {} isn't a valid coordinate", l, c)
+            }
+            CsvError::InvalidValue(l, e) => write!(f, "line {}: invalid value: {:?}", l, e),
+            CsvError::Insert(l, e) => write!(f, "line {}: {}", l, e),
+        }
+    }
+}
+
+impl<E> std::error::Error for CsvError<E>
+where
+    E: fmt::Debug,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CsvError::Io(e) => Some(e),
+            CsvError::Insert(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// An iterator which reads CSV rows into a [`Quadtree`], one per call to `next()`. Returned by
+/// [`Quadtree::read_csv()`].
+///
+/// Yields the handle of each inserted row, or the reason it wasn't inserted. A bad row doesn't
+/// stop the rows after it from being read, but an I/O error does.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`Quadtree::read_csv()`]: ../struct.Quadtree.html#method.read_csv
+#[must_use = "rows are only read as the iterator is consumed"]
+pub struct ReadCsv<'a, U, V, R, F>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a mut Quadtree<U, V>,
+    input: R,
+    format: Format,
+    parse: F,
+    // The number of lines read so far.
+    line: usize,
+    // Whether the next non-blank row is the header.
+    skip_header: bool,
+    done: bool,
+}
+
+impl<U, V, R, F, E> Iterator for ReadCsv<'_, U, V, R, F>
+where
+    U: PrimInt + Default + FromStr,
+    R: BufRead,
+    F: FnMut(&str) -> Result<V, E>,
+{
+    type Item = Result<u64, CsvError<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done {
+                return None;
+            }
+            let (line, fields) = match self.read_record() {
+                Ok(Some(record)) => record,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            if fields.len() == 1 && fields[0].trim().is_empty() {
+                continue;
+            }
+            if self.skip_header {
+                self.skip_header = false;
+                continue;
+            }
+            return Some(self.insert(line, &fields));
+        }
+    }
+}
+
+impl<U, V, R, F, E> ReadCsv<'_, U, V, R, F>
+where
+    U: PrimInt + Default + FromStr,
+    R: BufRead,
+    F: FnMut(&str) -> Result<V, E>,
+{
+    // Reads the next record, which may span several lines if it has quoted line breaks. Returns
+    // the line it starts on and its fields, or None at the end of the input.
+    fn read_record(&mut self) -> Result<Option<(usize, Vec<String>)>, CsvError<E>> {
+        let start = self.line + 1;
+        let mut text = String::new();
+        loop {
+            let read = self.input.read_line(&mut text).map_err(CsvError::Io)?;
+            if read == 0 {
+                return if text.is_empty() {
+                    Ok(None)
+                } else {
+                    Err(CsvError::UnterminatedQuote(start))
+                };
+            }
+            self.line += 1;
+            if let Some(fields) = split_record(&text, self.format.delimiter) {
+                return Ok(Some((start, fields)));
+            }
+        }
+    }
+
+    fn insert(&mut self, line: usize, fields: &[String]) -> Result<u64, CsvError<E>> {
+        let field = |column: usize| {
+            fields
+                .get(column)
+                .ok_or(CsvError::MissingColumn(line, column))
+        };
+        let number = |column: usize| -> Result<U, CsvError<E>> {
+            field(column)?
+                .trim()
+                .parse()
+                .map_err(|_| CsvError::InvalidNumber(line, column))
+        };
+
+        let format = &self.format;
+        let region = (
+            (number(format.x)?, number(format.y)?),
+            (number(format.width)?, number(format.height)?),
+        );
+        let value =
+            (self.parse)(field(format.value)?).map_err(|e| CsvError::InvalidValue(line, e))?;
+        self.qt
+            .try_insert(region, value)
+            .map_err(|e| CsvError::Insert(line, e))
+    }
+}
+
+/// Writes the given entries as CSV rows, i.e. the results of a [`.query()`] or [`.iter()`].
+/// Each value is written as the text returned by `show`. Columns which hold no field are left
+/// empty, and fields are quoted where necessary.
+///
+/// [`.query()`]: ../struct.Quadtree.html#method.query
+/// [`.iter()`]: ../struct.Quadtree.html#method.iter
+pub fn write<'a, U, V, I, W, S>(
+    entries: I,
+    mut output: W,
+    format: &Format,
+    mut show: S,
+) -> io::Result<()>
+where
+    U: PrimInt + Default + fmt::Display + 'a,
+    V: 'a,
+    I: IntoIterator<Item = &'a Entry<U, V>>,
+    W: Write,
+    S: FnMut(&V) -> String,
+{
+    let columns = format.columns();
+    let mut row = vec![String::new(); columns.iter().max().map_or(0, |m| m + 1)];
+    let mut write_row = |output: &mut W, fields: [String; 5]| {
+        for (column, field) in columns.iter().zip(fields.iter()) {
+            row[*column] = quote(field, format.delimiter);
+        }
+        writeln!(output, "{}", row.join(&format.delimiter.to_string()))
+    };
+
+    if format.header {
+        write_row(
+            &mut output,
+            ["x", "y", "width", "height", "value"].map(str::to_string),
+        )?;
+    }
+    for entry in entries {
+        write_row(
+            &mut output,
+            [
+                entry.anchor().x.to_string(),
+                entry.anchor().y.to_string(),
+                entry.width().to_string(),
+                entry.height().to_string(),
+                show(entry.value_ref()),
+            ],
+        )?;
+    }
+    output.flush()
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Returns an iterator which reads CSV rows from `input` and inserts each into the
+    /// quadtree as it's consumed. Each value is parsed from its field by `parse`. See the
+    /// [`csv`] module.
+    /// ```
+    /// use quadtree_rs::{
+    ///     csv::{CsvError, Format},
+    ///     Quadtree,
+    /// };
+    ///
+    /// let mut qt = Quadtree::<u8, u32>::new(4);
+    /// let mut rows = qt.read_csv(
+    ///     "1,1,1,1,10\n1,x,1,1,20\n1,1,1,1,thirty\n".as_bytes(),
+    ///     &Format::default(),
+    ///     |s| s.parse::<u32>(),
+    /// );
+    ///
+    /// assert!(rows.next().unwrap().is_ok());
+    /// assert!(matches!(rows.next(), Some(Err(CsvError::InvalidNumber(2, 1)))));
+    /// assert!(matches!(rows.next(), Some(Err(CsvError::InvalidValue(3, _)))));
+    /// assert!(rows.next().is_none());
+    /// ```
+    ///
+    /// [`csv`]: csv/index.html
+    pub fn read_csv<R, F, E>(
+        &mut self,
+        input: R,
+        format: &Format,
+        parse: F,
+    ) -> ReadCsv<'_, U, V, R, F>
+    where
+        R: BufRead,
+        F: FnMut(&str) -> Result<V, E>,
+    {
+        ReadCsv {
+            qt: self,
+            input,
+            format: format.clone(),
+            parse,
+            line: 0,
+            skip_header: format.header,
+            done: false,
+        }
+    }
+
+    /// Writes every entry, in handle order, as CSV rows. See [`write()`].
+    ///
+    /// [`write()`]: csv/fn.write.html
+    pub fn write_csv<W, S>(&self, output: W, format: &Format, show: S) -> io::Result<()>
+    where
+        U: fmt::Display,
+        W: Write,
+        S: FnMut(&V) -> String,
+    {
+        write(self.entries_by_handle(), output, format, show)
+    }
+}
+
+// Splits @record, less its line break, into fields. Returns None if it ends inside a quoted
+// field, i.e. it continues on the next line.
+fn split_record(record: &str, delimiter: char) -> Option<Vec<String>> {
+    let record = record.strip_suffix('\n').unwrap_or(record);
+    let record = record.strip_suffix('\r').unwrap_or(record);
+
+    let mut fields = vec![String::new()];
+    let mut chars = record.chars().peekable();
+    let mut quoted = false;
+    let mut at_field_start = true;
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => field.push(c),
+            }
+        } else if c == delimiter {
+            fields.push(String::new());
+            at_field_start = true;
+            continue;
+        } else if c == '"' && at_field_start {
+            quoted = true;
+        } else {
+            field.push(c);
+        }
+        at_field_start = false;
+    }
+    if quoted {
+        None
+    } else {
+        Some(fields)
+    }
+}
+
+// Quotes @field if it holds a quote, the delimiter, or a line break.
+fn quote(field: &str, delimiter: char) -> String {
+    if field.contains(['"', delimiter, '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
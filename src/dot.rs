@@ -0,0 +1,119 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [Graphviz](https://graphviz.org/) DOT export of a [`Quadtree`]'s node hierarchy.
+//!
+//! Every node which has been subdivided into becomes a graph node, labelled with its region,
+//! its depth, and the number of handles it holds. Nodes which hold handles are shaded. Each
+//! node has an edge to each of its four subquadrants, labelled `ne`, `nw`, `se`, or `sw`.
+//!
+//! The graph itself is labelled with the number of entries and the total number of handles
+//! held across all nodes. A region which can't be held by a single node has its handle
+//! replicated, so the second number is always at least the first.
+//! ```
+//! use quadtree_rs::Quadtree;
+//!
+//! let mut qt = Quadtree::<u8, ()>::new(1);
+//! qt.insert(((0, 0), (2, 1)), ());
+//!
+//! let dot = qt.to_dot();
+//! assert!(dot.starts_with("digraph quadtree {"));
+//! assert!(dot.contains(r#"label="1 entries, 2 handles";"#));
+//! assert!(dot.contains(r#"n0 -> n1 [label="ne"];"#));
+//! ```
+//!
+//! Render the output with e.g. `dot -Tsvg tree.dot > tree.svg`.
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+
+use crate::{
+    qtinner::QTInner,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::{
+    default::Default,
+    fmt::{
+        Debug,
+        Write,
+    },
+};
+
+// The names of the subquadrants, in the order QTInner holds them.
+const QUADRANTS: [&str; 4] = ["ne", "nw", "se", "sw"];
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default + Debug,
+{
+    /// Writes the node hierarchy as a Graphviz DOT `digraph`. See the [`dot`] module.
+    ///
+    /// Graph nodes are named `n0`, `n1`, ... in pre-order, so `n0` is always the root.
+    ///
+    /// [`dot`]: dot/index.html
+    pub fn to_dot(&self) -> String {
+        let mut nodes = String::new();
+        let mut count = 0;
+        let handles = write_node(&mut nodes, self.inner(), &mut count);
+
+        let mut dot = String::new();
+        dot.push_str("digraph quadtree {\n");
+        let _ = writeln!(
+            dot,
+            "  label=\"{} entries, {} handles\";",
+            self.len(),
+            handles
+        );
+        dot.push_str("  node [shape=box, style=filled, fillcolor=white];\n");
+        dot.push_str(&nodes);
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// Writes @qt, its subquadrants, and the edges between them to @dot. @count is the number of
+// nodes written so far, and so the id of @qt. Returns the number of handles held by @qt and its
+// subquadrants.
+fn write_node<U>(dot: &mut String, qt: &QTInner<U>, count: &mut usize) -> usize
+where
+    U: PrimInt + Default + Debug,
+{
+    let id = *count;
+    *count += 1;
+
+    let held = qt.handles().len();
+    let _ = writeln!(
+        dot,
+        "  n{} [label=\"{:?}\\ndepth {}\\n{} handles\"{}];",
+        id,
+        qt.region(),
+        qt.depth(),
+        held,
+        if held > 0 {
+            ", fillcolor=lightblue"
+        } else {
+            ""
+        },
+    );
+
+    let mut total = held;
+    if let Some(sqs) = qt.subquadrants() {
+        for (sq, name) in sqs.iter().zip(QUADRANTS.iter()) {
+            let child = *count;
+            total += write_node(dot, sq, count);
+            let _ = writeln!(dot, "  n{} -> n{} [label=\"{}\"];", id, child, name);
+        }
+    }
+    total
+}
@@ -18,6 +18,7 @@
 use crate::{
     area::Area,
     point::Point,
+    Quadtree,
 };
 use num::PrimInt;
 #[cfg(feature = "serde")]
@@ -25,7 +26,10 @@ use serde::{
     Deserialize,
     Serialize,
 };
-use std::default::Default;
+use std::{
+    default::Default,
+    fmt::Debug,
+};
 
 /// A region/value association in the [`Quadtree`].
 ///
@@ -42,7 +46,7 @@ use std::default::Default;
 /// };
 ///
 /// let mut qt = Quadtree::<u32, f64>::new(4);
-/// let region_a: Area<u32> = ((1,1),(3,2)).into();
+/// let region_a: Area<u32> = Area::new((1,1), (3,2)).unwrap();
 ///
 /// qt.insert(region_a, 4.56_f64);
 ///
@@ -66,7 +70,7 @@ use std::default::Default;
 /// [`Quadtree`]: ../struct.Quadtree.html
 // TODO(ambuc): Entry should hold Box<V> for better return-by-value semantics.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Entry<U, V>
 where
     U: PrimInt + Default,
@@ -125,7 +129,247 @@ where
         self.area().dimensions()
     }
 
+    pub(crate) fn set_area(&mut self, region: Area<U>) {
+        self.region = region;
+    }
+
+    pub(crate) fn into_value(self) -> V {
+        self.value
+    }
+
     pub fn handle(&self) -> u64 {
         self.handle
     }
 }
+
+/// A view into a single region of a [`Quadtree`], which may either be vacant or occupied.
+///
+/// This enum is constructed from the [`.entry()`] method on [`Quadtree`]. A region is occupied if
+/// some value is associated with exactly that region; regions which merely overlap do not count.
+///
+/// [`.entry()`]: ../struct.Quadtree.html#method.entry
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub enum RegionEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// An occupied region.
+    Occupied(OccupiedEntry<'a, U, V>),
+    /// A vacant region.
+    Vacant(VacantEntry<'a, U, V>),
+}
+
+impl<'a, U, V> RegionEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Ensures a value is associated with the region by inserting the default if empty, and
+    /// returns a mutable reference to the value.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        match self {
+            RegionEntry::Occupied(entry) => entry.into_mut(),
+            RegionEntry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    /// Ensures a value is associated with the region by inserting the result of the default
+    /// function if empty, and returns a mutable reference to the value.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            RegionEntry::Occupied(entry) => entry.into_mut(),
+            RegionEntry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    /// Provides in-place mutable access to an occupied entry before any potential inserts.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        match self {
+            RegionEntry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                RegionEntry::Occupied(entry)
+            }
+            RegionEntry::Vacant(entry) => RegionEntry::Vacant(entry),
+        }
+    }
+
+    /// The region this entry refers to.
+    pub fn area(&self) -> Area<U> {
+        match self {
+            RegionEntry::Occupied(entry) => entry.area(),
+            RegionEntry::Vacant(entry) => entry.area(),
+        }
+    }
+
+    // pub(crate)
+
+    // Callers must ensure that @area fits within @qt.
+    pub(crate) fn new(qt: &'a mut Quadtree<U, V>, area: Area<U>) -> Self {
+        let handle = qt
+            .query_strict(area)
+            .filter(|e| e.area() == area)
+            .map(|e| e.handle())
+            .min();
+        match handle {
+            Some(handle) => RegionEntry::Occupied(OccupiedEntry { qt, handle }),
+            None => RegionEntry::Vacant(VacantEntry { qt, area }),
+        }
+    }
+}
+
+impl<'a, U, V> RegionEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+    V: Default,
+{
+    /// Ensures a value is associated with the region by inserting `V::default()` if empty, and
+    /// returns a mutable reference to the value.
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<U, V> Debug for RegionEntry<'_, U, V>
+where
+    U: PrimInt + Default + Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RegionEntry::Occupied(entry) => f.debug_tuple("Occupied").field(entry).finish(),
+            RegionEntry::Vacant(entry) => f.debug_tuple("Vacant").field(entry).finish(),
+        }
+    }
+}
+
+/// A view into an occupied region of a [`Quadtree`]. It is part of the [`RegionEntry`] enum.
+///
+/// If several values are associated with exactly the same region, this refers to the one which
+/// was inserted first.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`RegionEntry`]: enum.RegionEntry.html
+pub struct OccupiedEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a mut Quadtree<U, V>,
+    handle: u64,
+}
+
+impl<'a, U, V> OccupiedEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// The handle of the value associated with this region.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// The region this entry refers to.
+    pub fn area(&self) -> Area<U> {
+        self.entry().area()
+    }
+
+    /// A reference to the value associated with this region.
+    pub fn get(&self) -> &V {
+        self.entry().value_ref()
+    }
+
+    /// A mutable reference to the value associated with this region.
+    pub fn get_mut(&mut self) -> &mut V {
+        self.qt
+            .get_mut(self.handle)
+            .expect("An OccupiedEntry's handle should always be in the store.")
+            .value_mut()
+    }
+
+    /// Converts the entry into a mutable reference to the value, bound to the lifetime of the
+    /// quadtree itself.
+    pub fn into_mut(self) -> &'a mut V {
+        self.qt
+            .get_mut(self.handle)
+            .expect("An OccupiedEntry's handle should always be in the store.")
+            .value_mut()
+    }
+
+    /// Replaces the value associated with this region, returning the old value. The handle is
+    /// unchanged.
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    /// Removes this region/value association from the quadtree.
+    pub fn remove(self) -> Entry<U, V> {
+        self.qt
+            .delete_by_handle(self.handle)
+            .expect("An OccupiedEntry's handle should always be in the store.")
+    }
+
+    fn entry(&self) -> &Entry<U, V> {
+        self.qt
+            .get(self.handle)
+            .expect("An OccupiedEntry's handle should always be in the store.")
+    }
+}
+
+impl<U, V> Debug for OccupiedEntry<'_, U, V>
+where
+    U: PrimInt + Default + Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("area", &self.area())
+            .field("value", self.get())
+            .finish()
+    }
+}
+
+/// A view into a vacant region of a [`Quadtree`]. It is part of the [`RegionEntry`] enum.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`RegionEntry`]: enum.RegionEntry.html
+pub struct VacantEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a mut Quadtree<U, V>,
+    area: Area<U>,
+}
+
+impl<'a, U, V> VacantEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// The region this entry refers to.
+    pub fn area(&self) -> Area<U> {
+        self.area
+    }
+
+    /// Associates a value with this region, and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        let handle = self
+            .qt
+            .insert(self.area, value)
+            .expect("A VacantEntry's region should always fit in the quadtree.");
+        self.qt
+            .get_mut(handle)
+            .expect("A freshly-inserted handle should always be in the store.")
+            .value_mut()
+    }
+}
+
+impl<U, V> Debug for VacantEntry<'_, U, V>
+where
+    U: PrimInt + Default + Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("VacantEntry").field(&self.area).finish()
+    }
+}
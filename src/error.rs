@@ -0,0 +1,60 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Errors returned by fallible operations on the tree.
+
+use std::{
+    convert::Infallible,
+    fmt,
+};
+
+/// The error type for fallible operations on a [`Quadtree`] and its regions.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The region is too large for, or doesn't overlap with, the region which the quadtree
+    /// represents.
+    OutOfBounds,
+    /// The region has a nonpositive width or height.
+    InvalidDimensions,
+    /// Some coordinate could not be represented by the coordinate type `U`.
+    Overflow,
+    /// No value is associated with the given handle.
+    UnknownHandle(u64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::OutOfBounds => write!(f, "region does not fit within the quadtree"),
+            Error::InvalidDimensions => {
+                write!(f, "regions may not have nonpositive widths or heights")
+            }
+            Error::Overflow => write!(f, "coordinate overflowed the coordinate type"),
+            Error::UnknownHandle(handle) => write!(f, "no value with handle {}", handle),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Lets infallible conversions (i.e. `From<Point<U>> for Area<U>`) be used wherever a fallible
+// `TryInto<Area<U>>` is accepted.
+impl From<Infallible> for Error {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
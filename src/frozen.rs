@@ -0,0 +1,708 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A frozen, read-only quadtree which is queried in place from a flat byte layout.
+//!
+//! [`Quadtree::freeze()`] writes a quadtree's nodes and entries as fixed-size records which
+//! refer to each other by index rather than by pointer. The bytes can be written to a file, and
+//! later memory-mapped (with e.g. the `memmap2` crate) and opened with
+//! [`FrozenQuadtree::open()`], which only checks the header and section sizes. Queries then walk
+//! the records where they lie: nothing is deserialized up front, and values are only decoded
+//! when asked for.
+//!
+//! Every read from the bytes is bounds-checked, so a corrupt file can't cause a panic or
+//! undefined behaviour. Records which are out of range or otherwise malformed are skipped.
+//!
+//! # Layout
+//!
+//! All integers are little-endian. Coordinates are written in the width of the coordinate type
+//! `U`, as in the [binary format].
+//!
+//! | Section     | Field                 | Encoding                                          |
+//! |-------------|-----------------------|---------------------------------------------------|
+//! | Header      | magic                 | the four bytes `QTFZ`                             |
+//! |             | version               | `u16`, currently `1`                              |
+//! |             | flags                 | `u16`, currently `0`                              |
+//! |             | coordinate width      | `u8`, in bytes                                    |
+//! |             | coordinate signedness | `u8`, `1` if signed                               |
+//! |             | reserved              | `u16`, `0`                                        |
+//! |             | depth                 | `u32`                                             |
+//! |             | anchor                | two coordinates, `x` then `y`                     |
+//! |             | counts                | `u64` each: nodes, handle references, entries, value bytes |
+//! | Nodes       | one record per node   | `u64` first handle reference, `u64` handle reference count, `u64` index of first subquadrant, or `0` |
+//! | References  | one per held handle   | `u64` index into the entry table                  |
+//! | Entries     | one record per entry  | `u64` handle, four coordinates (`x`, `y`, width, height), `u64` value offset, `u64` value length |
+//! | Values      | the encoded values    | bytes, as written by [`Codec`]                    |
+//!
+//! Node records are written breadth-first from the root, so the four subquadrants of a node are
+//! consecutive, in the order northeast, northwest, southeast, southwest. Entry records are in
+//! handle order.
+//!
+//! [`Quadtree::freeze()`]: ../struct.Quadtree.html#method.freeze
+//! [`FrozenQuadtree::open()`]: struct.FrozenQuadtree.html#method.open
+//! [binary format]: ../binary/index.html
+//! [`Codec`]: ../binary/trait.Codec.html
+
+use crate::{
+    area::Area,
+    binary::{
+        self,
+        Codec,
+        DecodeError,
+    },
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    traversal::Traversal,
+};
+use num::PrimInt;
+use std::{
+    collections::HashMap,
+    convert::{
+        TryFrom,
+        TryInto,
+    },
+    default::Default,
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    mem,
+};
+
+const MAGIC: &[u8; 4] = b"QTFZ";
+/// The version of the frozen layout written by this crate.
+pub const VERSION: u16 = 1;
+const NODE_LEN: usize = 24;
+const REFERENCE_LEN: usize = 8;
+
+/// A read-only quadtree which borrows a frozen byte layout and queries it in place. See the
+/// [`frozen`] module.
+///
+/// Handles are the same as in the [`Quadtree`] which was frozen.
+/// ```
+/// use quadtree_rs::{frozen::FrozenQuadtree, Quadtree};
+///
+/// let mut qt = Quadtree::<u32, String>::new(4);
+/// let lake = qt.insert(((1, 1), (4, 4)), "lake".to_string()).unwrap();
+/// qt.insert((9, 9), "well".to_string());
+///
+/// // These bytes could just as well be a memory-mapped file.
+/// let bytes = qt.freeze();
+/// let frozen = FrozenQuadtree::<u32, String>::open(&bytes).unwrap();
+///
+/// let hit = frozen.query_pt((2, 2)).next().unwrap();
+/// assert_eq!(hit.handle(), lake);
+/// assert_eq!(hit.value(), Some("lake".to_string()));
+/// assert_eq!(frozen.query(((0, 0), (16, 16))).count(), 2);
+/// ```
+///
+/// [`frozen`]: index.html
+/// [`Quadtree`]: ../struct.Quadtree.html
+pub struct FrozenQuadtree<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    region: Area<U>,
+    depth: usize,
+    nodes: &'a [u8],
+    references: &'a [u8],
+    entries: &'a [u8],
+    values: &'a [u8],
+    _value: PhantomData<fn() -> V>,
+}
+
+impl<U, V> Clone for FrozenQuadtree<'_, U, V>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U, V> Copy for FrozenQuadtree<'_, U, V> where U: PrimInt + Default {}
+
+impl<U, V> fmt::Debug for FrozenQuadtree<'_, U, V>
+where
+    U: PrimInt + Default + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FrozenQuadtree")
+            .field("region", &self.region)
+            .field("depth", &self.depth)
+            .field("nodes", &(self.nodes.len() / NODE_LEN))
+            .field("entries", &self.len())
+            .finish()
+    }
+}
+
+impl<'a, U, V> FrozenQuadtree<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Opens bytes written by [`Quadtree::freeze()`].
+    ///
+    /// Only the header and the section sizes are checked, so this takes constant time however
+    /// large the tree is.
+    /// ```
+    /// use quadtree_rs::{binary::DecodeError, frozen::FrozenQuadtree, Quadtree};
+    ///
+    /// let bytes = Quadtree::<u8, ()>::new(2).freeze();
+    /// assert!(FrozenQuadtree::<u8, ()>::open(&bytes).is_ok());
+    /// assert_eq!(
+    ///     FrozenQuadtree::<u16, ()>::open(&bytes).unwrap_err(),
+    ///     DecodeError::CoordinateMismatch
+    /// );
+    /// assert_eq!(
+    ///     FrozenQuadtree::<u8, ()>::open(&bytes[1..]).unwrap_err(),
+    ///     DecodeError::BadMagic
+    /// );
+    /// ```
+    ///
+    /// [`Quadtree::freeze()`]: ../struct.Quadtree.html#method.freeze
+    pub fn open(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        let mut r = Sections { bytes };
+
+        if r.take(4)? != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let version = r.u64_of(2)? as u16;
+        if version > VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let flags = r.u64_of(2)? as u16;
+        if flags != 0 {
+            return Err(DecodeError::UnsupportedFlags(flags));
+        }
+        let format = r.take(2)?;
+        if (format[0], format[1]) != binary::coordinate_format::<U>() {
+            return Err(DecodeError::CoordinateMismatch);
+        }
+        r.take(2)?;
+        let depth = r.u64_of(4)? as usize;
+        let width = mem::size_of::<U>();
+        let anchor = Point {
+            x: binary::read_coord(r.take(width)?).ok_or(DecodeError::CoordinateMismatch)?,
+            y: binary::read_coord(r.take(width)?).ok_or(DecodeError::CoordinateMismatch)?,
+        };
+        let region = QTInner::try_new(anchor, depth)
+            .map_err(|_| DecodeError::InvalidRoot)?
+            .region();
+
+        let node_count = r.u64_of(8)?;
+        let reference_count = r.u64_of(8)?;
+        let entry_count = r.u64_of(8)?;
+        let value_len = r.u64_of(8)?;
+        let nodes = r.section(node_count, NODE_LEN)?;
+        let references = r.section(reference_count, REFERENCE_LEN)?;
+        let entries = r.section(entry_count, entry_len::<U>())?;
+        let values = r.section(value_len, 1)?;
+        if !r.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        Ok(FrozenQuadtree {
+            region,
+            depth,
+            nodes,
+            references,
+            entries,
+            values,
+            _value: PhantomData,
+        })
+    }
+
+    /// The top-left corner of the region covered by the quadtree.
+    pub fn anchor(&self) -> Point<U> {
+        self.region.anchor()
+    }
+
+    /// The width of the region covered by the quadtree.
+    pub fn width(&self) -> usize {
+        self.region.width().to_usize().unwrap_or(usize::MAX)
+    }
+
+    /// The height of the region covered by the quadtree.
+    pub fn height(&self) -> usize {
+        self.region.height().to_usize().unwrap_or(usize::MAX)
+    }
+
+    /// The depth of the quadtree.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The number of entries in the quadtree.
+    pub fn len(&self) -> usize {
+        self.entries.len() / entry_len::<U>()
+    }
+
+    /// Whether the quadtree has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Looks up an entry by its handle, in O(log n) time.
+    pub fn get(&self, handle: u64) -> Option<FrozenEntry<'a, U, V>> {
+        let (mut lo, mut hi) = (0, self.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let at = self.entries.get(mid * entry_len::<U>()..)?;
+            match read_u64(at, 0)?.cmp(&handle) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return self.entry(mid),
+            }
+        }
+        None
+    }
+
+    /// Returns an iterator over the entries whose regions intersect the given region. See
+    /// [`Quadtree::query()`].
+    ///
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: impl TryInto<Area<U>>) -> Query<'a, U, V> {
+        Query::new(*self, area.try_into().ok(), Traversal::Overlapping)
+    }
+
+    /// Returns an iterator over the entries whose regions are contained by the given region.
+    /// See [`Quadtree::query_strict()`].
+    ///
+    /// [`Quadtree::query_strict()`]: ../struct.Quadtree.html#method.query_strict
+    pub fn query_strict(&self, area: impl TryInto<Area<U>>) -> Query<'a, U, V> {
+        Query::new(*self, area.try_into().ok(), Traversal::Strict)
+    }
+
+    /// Returns an iterator over the entries whose regions contain the given point. Only one
+    /// path from the root is walked.
+    pub fn query_pt(&self, pt: impl Into<Point<U>>) -> Query<'a, U, V> {
+        let area = Area::new(pt.into(), (U::one(), U::one())).ok();
+        Query::new(*self, area, Traversal::Overlapping)
+    }
+
+    /// Returns an iterator over every entry, in handle order.
+    pub fn iter(&self) -> Iter<'a, U, V> {
+        Iter { qt: *self, i: 0 }
+    }
+
+    // fn
+
+    // The entry at index @i of the entry table, if its record is well-formed.
+    fn entry(&self, i: usize) -> Option<FrozenEntry<'a, U, V>> {
+        let len = entry_len::<U>();
+        let record = self.entries.get(i.checked_mul(len)?..)?.get(..len)?;
+        let width = mem::size_of::<U>();
+        let coord = |n: usize| binary::read_coord::<U>(&record[8 + n * width..8 + (n + 1) * width]);
+        let area = Area::new((coord(0)?, coord(1)?), (coord(2)?, coord(3)?))
+            .ok()
+            .filter(|area| self.region.contains(*area))?;
+        let offset: usize = read_u64(record, 8 + 4 * width)?.try_into().ok()?;
+        let value_len: usize = read_u64(record, 16 + 4 * width)?.try_into().ok()?;
+        Some(FrozenEntry {
+            handle: read_u64(record, 0)?,
+            area,
+            value: self.values.get(offset..offset.checked_add(value_len)?)?,
+            _value: PhantomData,
+        })
+    }
+
+    // The range of handle references held by node @i, and the index of its first subquadrant.
+    fn node(&self, i: usize) -> Option<(usize, usize, usize)> {
+        let record = self
+            .nodes
+            .get(i.checked_mul(NODE_LEN)?..)?
+            .get(..NODE_LEN)?;
+        let first: usize = read_u64(record, 0)?.try_into().ok()?;
+        let count: usize = read_u64(record, 8)?.try_into().ok()?;
+        let children = read_u64(record, 16)?.try_into().ok()?;
+        // Clamp the range, so that a corrupt count can't send a query through a long run of
+        // missing references.
+        let end = first
+            .saturating_add(count)
+            .min(self.references.len() / REFERENCE_LEN);
+        Some((first, end, children))
+    }
+
+    // The entry table index of handle reference @i.
+    fn reference(&self, i: usize) -> Option<usize> {
+        let at = self.references.get(i.checked_mul(REFERENCE_LEN)?..)?;
+        read_u64(at, 0)?.try_into().ok()
+    }
+}
+
+/// An entry in a [`FrozenQuadtree`], which borrows its value's encoded bytes.
+///
+/// [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+pub struct FrozenEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    handle: u64,
+    area: Area<U>,
+    value: &'a [u8],
+    _value: PhantomData<fn() -> V>,
+}
+
+impl<U, V> Clone for FrozenEntry<'_, U, V>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<U, V> Copy for FrozenEntry<'_, U, V> where U: PrimInt + Default {}
+
+impl<U, V> fmt::Debug for FrozenEntry<'_, U, V>
+where
+    U: PrimInt + Default + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FrozenEntry")
+            .field("handle", &self.handle)
+            .field("area", &self.area)
+            .field("value", &self.value)
+            .finish()
+    }
+}
+
+impl<'a, U, V> FrozenEntry<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// The handle of the entry.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// The region of the entry.
+    pub fn area(&self) -> Area<U> {
+        self.area
+    }
+
+    /// The top-left corner of the region of the entry.
+    pub fn anchor(&self) -> Point<U> {
+        self.area.anchor()
+    }
+
+    /// The width of the region of the entry.
+    pub fn width(&self) -> U {
+        self.area.width()
+    }
+
+    /// The height of the region of the entry.
+    pub fn height(&self) -> U {
+        self.area.height()
+    }
+
+    /// The encoded bytes of the value, borrowed from the frozen layout.
+    pub fn value_bytes(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Decodes the value, or returns `None` if its bytes are invalid.
+    pub fn value(&self) -> Option<V>
+    where
+        V: Codec,
+    {
+        V::decode(self.value)
+    }
+}
+
+/// An iterator over the entries of a [`FrozenQuadtree`] which match a query. Each entry is
+/// returned once, however many nodes hold its handle.
+///
+/// Returned by [`.query()`], [`.query_strict()`], and [`.query_pt()`].
+///
+/// [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+/// [`.query()`]: struct.FrozenQuadtree.html#method.query
+/// [`.query_strict()`]: struct.FrozenQuadtree.html#method.query_strict
+/// [`.query_pt()`]: struct.FrozenQuadtree.html#method.query_pt
+pub struct Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: FrozenQuadtree<'a, U, V>,
+    // A @query_region of None (i.e. one which couldn't be built) matches nothing.
+    query_region: Option<Area<U>>,
+    traversal: Traversal,
+    // Nodes still to visit, as (index, region, depth).
+    stack: Vec<(usize, Area<U>, usize)>,
+    // The region of the node being visited, and the range of its handle references left to
+    // check.
+    current: Option<(Area<U>, usize, usize)>,
+    // A query visits each node at most once, so a corrupt layout which leads it to more nodes
+    // than there are is cut short.
+    visits_left: usize,
+}
+
+impl<'a, U, V> Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    fn new(
+        qt: FrozenQuadtree<'a, U, V>,
+        query_region: Option<Area<U>>,
+        traversal: Traversal,
+    ) -> Self {
+        let stack = match query_region {
+            Some(q) if q.intersects(qt.region) => vec![(0, qt.region, qt.depth)],
+            _ => vec![],
+        };
+        Query {
+            qt,
+            query_region,
+            traversal,
+            stack,
+            current: None,
+            visits_left: qt.nodes.len() / NODE_LEN,
+        }
+    }
+
+    // Pushes the subquadrants of node @i, at @region and @depth, which intersect the query.
+    fn push_subquadrants(&mut self, i: usize, children: usize, region: Area<U>, depth: usize) {
+        // Breadth-first order puts every subquadrant after its parent.
+        if children <= i || depth == 0 {
+            return;
+        }
+        let query = match self.query_region {
+            Some(q) => q,
+            None => return,
+        };
+        let two = U::one() + U::one();
+        let (w, h) = (region.width() / two, region.height() / two);
+        let (x, y) = (region.anchor().x(), region.anchor().y());
+        // [ne, nw, se, sw], as laid out by QTInner.
+        for (n, (x, y)) in [(x + w, y), (x, y), (x + w, y + h), (x, y + h)]
+            .iter()
+            .enumerate()
+        {
+            // A corrupt offset may run off the end of usize.
+            let child = match children.checked_add(n) {
+                Some(child) => child,
+                None => return,
+            };
+            let sq = Area::new_unchecked((*x, *y).into(), (w, h));
+            if sq.intersects(query) {
+                self.stack.push((child, sq, depth - 1));
+            }
+        }
+    }
+}
+
+impl<'a, U, V> Iterator for Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = FrozenEntry<'a, U, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let query = self.query_region?;
+        loop {
+            if let Some((region, i, end)) = self.current {
+                if i < end {
+                    self.current = Some((region, i + 1, end));
+                    let entry = match self.qt.reference(i).and_then(|e| self.qt.entry(e)) {
+                        Some(entry) => entry,
+                        None => continue,
+                    };
+                    if !self.traversal.eval(entry.area, query) {
+                        continue;
+                    }
+                    if region.holds_overlap_corner(entry.area, query) {
+                        return Some(entry);
+                    }
+                    continue;
+                }
+                self.current = None;
+            }
+
+            if self.visits_left == 0 {
+                return None;
+            }
+            self.visits_left -= 1;
+            let (i, region, depth) = self.stack.pop()?;
+            if let Some((first, end, children)) = self.qt.node(i) {
+                self.push_subquadrants(i, children, region, depth);
+                self.current = Some((region, first, end));
+            }
+        }
+    }
+}
+
+impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
+
+/// An iterator over every entry of a [`FrozenQuadtree`], in handle order.
+///
+/// Returned by [`.iter()`].
+///
+/// [`FrozenQuadtree`]: struct.FrozenQuadtree.html
+/// [`.iter()`]: struct.FrozenQuadtree.html#method.iter
+pub struct Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: FrozenQuadtree<'a, U, V>,
+    i: usize,
+}
+
+impl<'a, U, V> Iterator for Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = FrozenEntry<'a, U, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.i < self.qt.len() {
+            self.i += 1;
+            if let Some(entry) = self.qt.entry(self.i - 1) {
+                return Some(entry);
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.qt.len() - self.i))
+    }
+}
+
+impl<U, V> FusedIterator for Iter<'_, U, V> where U: PrimInt + Default {}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Codec,
+{
+    /// Writes this quadtree in the frozen layout, to be opened with
+    /// [`FrozenQuadtree::open()`]. See the [`frozen`] module.
+    ///
+    /// [`FrozenQuadtree::open()`]: frozen/struct.FrozenQuadtree.html#method.open
+    /// [`frozen`]: frozen/index.html
+    pub fn freeze(&self) -> Vec<u8> {
+        let inner = self.inner();
+
+        let entries = self.entries_by_handle();
+        let index: HashMap<u64, usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (e.handle(), i))
+            .collect();
+
+        // Breadth-first, so that siblings are consecutive.
+        let mut order: Vec<&QTInner<U>> = vec![inner];
+        let mut children = vec![];
+        let mut i = 0;
+        while i < order.len() {
+            match order[i].subquadrants() {
+                Some(sqs) => {
+                    children.push(order.len() as u64);
+                    order.extend(sqs.iter());
+                }
+                None => children.push(0),
+            }
+            i += 1;
+        }
+
+        let mut nodes = Vec::with_capacity(order.len() * NODE_LEN);
+        let mut references = vec![];
+        for (node, children) in order.iter().zip(children) {
+            nodes.extend_from_slice(&((references.len() / REFERENCE_LEN) as u64).to_le_bytes());
+            nodes.extend_from_slice(&(node.handles().len() as u64).to_le_bytes());
+            nodes.extend_from_slice(&children.to_le_bytes());
+            for handle in node.handles() {
+                references.extend_from_slice(&(index[handle] as u64).to_le_bytes());
+            }
+        }
+
+        let mut entry_table = Vec::with_capacity(entries.len() * entry_len::<U>());
+        let mut values = vec![];
+        for entry in &entries {
+            let offset = values.len();
+            entry.value_ref().encode(&mut values);
+            entry_table.extend_from_slice(&entry.handle().to_le_bytes());
+            binary::write_coord(&mut entry_table, entry.anchor().x());
+            binary::write_coord(&mut entry_table, entry.anchor().y());
+            binary::write_coord(&mut entry_table, entry.width());
+            binary::write_coord(&mut entry_table, entry.height());
+            entry_table.extend_from_slice(&(offset as u64).to_le_bytes());
+            entry_table.extend_from_slice(&((values.len() - offset) as u64).to_le_bytes());
+        }
+
+        let mut out = vec![];
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        let (width, signed) = binary::coordinate_format::<U>();
+        out.extend_from_slice(&[width, signed, 0, 0]);
+        out.extend_from_slice(&(inner.depth() as u32).to_le_bytes());
+        binary::write_coord(&mut out, inner.region().anchor().x());
+        binary::write_coord(&mut out, inner.region().anchor().y());
+        for count in &[
+            order.len(),
+            references.len() / REFERENCE_LEN,
+            entries.len(),
+            values.len(),
+        ] {
+            out.extend_from_slice(&(*count as u64).to_le_bytes());
+        }
+        out.extend_from_slice(&nodes);
+        out.extend_from_slice(&references);
+        out.extend_from_slice(&entry_table);
+        out.extend_from_slice(&values);
+        out
+    }
+}
+
+// The length of an entry record: a handle, four coordinates, and a value offset and length.
+fn entry_len<U>() -> usize {
+    8 + 4 * mem::size_of::<U>() + 16
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+}
+
+// Splits the frozen layout into its sections.
+struct Sections<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Sections<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    // Reads an unsigned integer @n bytes wide.
+    fn u64_of(&mut self, n: usize) -> Result<u64, DecodeError> {
+        let mut buf = [0; 8];
+        buf[..n].copy_from_slice(self.take(n)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    // Takes a section of @count records, each @len bytes long.
+    fn section(&mut self, count: u64, len: usize) -> Result<&'a [u8], DecodeError> {
+        let size = usize::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(len))
+            .ok_or(DecodeError::Truncated)?;
+        self.take(size)
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Import and export of [GeoJSON](https://datatracker.ietf.org/doc/html/rfc7946)
+//! `FeatureCollection`s. Requires the `geojson` feature.
+//!
+//! On import, each feature's geometry is reduced to its integer bounding box, and its
+//! `properties` are deserialized as the value. On export, each entry becomes a feature with a
+//! rectangular `Polygon` geometry, its handle as the feature's `id`, and its value serialized as
+//! the feature's `properties`.
+//!
+//! GeoJSON coordinates are `[x, y]` pairs. They're used as-is, so a tree with `y` growing
+//! downwards will appear flipped in tools which expect `y` to grow northwards.
+//! ```
+//! use quadtree_rs::{geojson, Quadtree};
+//!
+//! let mut qt = Quadtree::<i32, String>::new_with_anchor((-8, -8).into(), 4);
+//! let handles = qt
+//!     .insert_geojson(
+//!         r#"{
+//!             "type": "FeatureCollection",
+//!             "features": [
+//!                 {
+//!                     "type": "Feature",
+//!                     "geometry": {"type": "Point", "coordinates": [1.5, -2.5]},
+//!                     "properties": "well"
+//!                 }
+//!             ]
+//!         }"#,
+//!     )
+//!     .unwrap();
+//!
+//! let well = qt.get(handles[0]).unwrap();
+//! assert_eq!(well.anchor(), (1, -3).into());
+//! assert_eq!((well.width(), well.height()), (1, 1));
+//!
+//! let exported = geojson::feature_collection(qt.query((1, -3)));
+//! assert!(exported.contains(r#""properties":"well""#));
+//! ```
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    point::Point,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use serde::{
+    de::DeserializeOwned,
+    Serialize,
+};
+use serde_json::{
+    json,
+    Value,
+};
+use std::{
+    default::Default,
+    fmt,
+};
+
+/// The error returned when a GeoJSON document can't be loaded into a [`Quadtree`].
+///
+/// Feature indices count from zero, in document order.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GeoJsonError {
+    /// The document isn't valid JSON.
+    Json(serde_json::Error),
+    /// The document isn't a `FeatureCollection`.
+    NotAFeatureCollection,
+    /// The feature at this index has a missing, empty, or malformed geometry.
+    InvalidGeometry(usize),
+    /// The properties of the feature at this index couldn't be deserialized as the value type.
+    InvalidProperties(usize, serde_json::Error),
+    /// The bounding box of the feature at this index can't be inserted into the quadtree.
+    Insert(usize, Error),
+}
+
+impl fmt::Display for GeoJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeoJsonError::Json(e) => write!(f, "invalid JSON: {}", e),
+            GeoJsonError::NotAFeatureCollection => write!(f, "not a GeoJSON FeatureCollection"),
+            GeoJsonError::InvalidGeometry(i) => write!(f, "feature {}: invalid geometry", i),
+            GeoJsonError::InvalidProperties(i, e) => {
+                write!(f, "feature {}: invalid properties: {}", i, e)
+            }
+            GeoJsonError::Insert(i, e) => write!(f, "feature {}: {}", i, e),
+        }
+    }
+}
+
+impl std::error::Error for GeoJsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GeoJsonError::Json(e) | GeoJsonError::InvalidProperties(_, e) => Some(e),
+            GeoJsonError::Insert(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the given entries as a GeoJSON `FeatureCollection`, i.e. the results of a
+/// [`.query()`].
+///
+/// Values which fail to serialize are written with `null` properties.
+///
+/// [`.query()`]: ../struct.Quadtree.html#method.query
+pub fn feature_collection<'a, U, V, I>(entries: I) -> String
+where
+    U: PrimInt + Default + Serialize + 'a,
+    V: Serialize + 'a,
+    I: IntoIterator<Item = &'a Entry<U, V>>,
+{
+    let features: Vec<Value> = entries
+        .into_iter()
+        .map(|entry| {
+            let area = entry.area();
+            let (l, t, r, b) = (
+                area.left_edge(),
+                area.top_edge(),
+                area.right_edge(),
+                area.bottom_edge(),
+            );
+            json!({
+                "type": "Feature",
+                "id": entry.handle(),
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[l, t], [r, t], [r, b], [l, b], [l, t]]],
+                },
+                "properties": serde_json::to_value(entry.value_ref()).unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+    .to_string()
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Inserts every feature of a GeoJSON `FeatureCollection`, and returns their handles in
+    /// document order.
+    ///
+    /// Each feature is associated with the smallest integer region which covers the bounding box
+    /// of its geometry. A bounding box with no width or height is widened to one unit, so a
+    /// `Point` at `[x, y]` becomes the 1x1 region anchored at `(floor(x), floor(y))`. Any
+    /// geometry type is accepted, including a `GeometryCollection`. The feature's `properties`
+    /// are deserialized as the value.
+    ///
+    /// Either every feature is inserted, or, if any feature fails, none are.
+    pub fn insert_geojson(&mut self, geojson: &str) -> Result<Vec<u64>, GeoJsonError>
+    where
+        V: DeserializeOwned,
+    {
+        let document: Value = serde_json::from_str(geojson).map_err(GeoJsonError::Json)?;
+        if document["type"] != "FeatureCollection" {
+            return Err(GeoJsonError::NotAFeatureCollection);
+        }
+        let features = document["features"]
+            .as_array()
+            .ok_or(GeoJsonError::NotAFeatureCollection)?;
+
+        let mut parsed: Vec<(Area<U>, V)> = Vec::with_capacity(features.len());
+        for (i, feature) in features.iter().enumerate() {
+            let mut bounds = None;
+            extend_bounds(&feature["geometry"], &mut bounds)
+                .ok_or(GeoJsonError::InvalidGeometry(i))?;
+            let area = bounding_area(bounds.ok_or(GeoJsonError::InvalidGeometry(i))?)
+                .map_err(|e| GeoJsonError::Insert(i, e))?;
+            if !self.contains(area) {
+                return Err(GeoJsonError::Insert(i, Error::OutOfBounds));
+            }
+            let value = V::deserialize(&feature["properties"])
+                .map_err(|e| GeoJsonError::InvalidProperties(i, e))?;
+            parsed.push((area, value));
+        }
+
+        Ok(parsed
+            .into_iter()
+            .filter_map(|(area, value)| self.insert(area, value))
+            .collect())
+    }
+
+    /// Writes every entry as a GeoJSON `FeatureCollection`. See [`feature_collection()`].
+    ///
+    /// [`feature_collection()`]: geojson/fn.feature_collection.html
+    pub fn to_geojson(&self) -> String
+    where
+        U: Serialize,
+        V: Serialize,
+    {
+        feature_collection(self.iter())
+    }
+}
+
+// Grows @bounds, as (min_x, min_y, max_x, max_y), to cover every position in @geometry. Returns
+// None if the geometry is malformed.
+fn extend_bounds(geometry: &Value, bounds: &mut Option<(f64, f64, f64, f64)>) -> Option<()> {
+    if geometry["type"] == "GeometryCollection" {
+        for g in geometry["geometries"].as_array()? {
+            extend_bounds(g, bounds)?;
+        }
+        return Some(());
+    }
+    geometry["type"].as_str()?;
+    extend_bounds_by_coordinates(&geometry["coordinates"], bounds)
+}
+
+// Coordinates nest to different depths for each geometry type, but always bottom out in
+// positions: arrays of two or more numbers.
+fn extend_bounds_by_coordinates(
+    coordinates: &Value,
+    bounds: &mut Option<(f64, f64, f64, f64)>,
+) -> Option<()> {
+    let array = coordinates.as_array()?;
+    if let (Some(x), Some(y)) = (
+        array.first().and_then(Value::as_f64),
+        array.get(1).and_then(Value::as_f64),
+    ) {
+        let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+        *bounds = Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+        return Some(());
+    }
+    for c in array {
+        extend_bounds_by_coordinates(c, bounds)?;
+    }
+    Some(())
+}
+
+// The smallest integer region covering @bounds, which is at least 1x1.
+fn bounding_area<U>((min_x, min_y, max_x, max_y): (f64, f64, f64, f64)) -> Result<Area<U>, Error>
+where
+    U: PrimInt + Default,
+{
+    let span = |lo: f64, hi: f64| -> Result<(U, U), Error> {
+        let (lo, hi) = (lo.floor(), hi.ceil().max(lo.floor() + 1.0));
+        Ok((
+            U::from(lo).ok_or(Error::Overflow)?,
+            U::from(hi - lo).ok_or(Error::Overflow)?,
+        ))
+    };
+    let (x, width) = span(min_x, max_x)?;
+    let (y, height) = span(min_y, max_y)?;
+    Area::new(Point { x, y }, (width, height))
+}
@@ -48,6 +48,16 @@ where
         }
     }
 
+    // A HandleIter which yields nothing.
+    pub(crate) fn empty(search_area: Area<U>) -> HandleIter<'a, U> {
+        HandleIter {
+            search_area,
+            handle_stack: vec![],
+            qt_stack: vec![],
+            visited: HashSet::new(),
+        }
+    }
+
     // Descent is an optimization for queries. We don't want to traverse the entire tree searching
     // for handles which (mostly) correspond to regions our @req doesn't intersect with.
     //
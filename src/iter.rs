@@ -21,7 +21,13 @@ use crate::{
     types::StoreType,
 };
 use num::PrimInt;
-use std::iter::FusedIterator;
+use std::{
+    collections::{
+        hash_map,
+        HashSet,
+    },
+    iter::FusedIterator,
+};
 
 /// An iterator over all regions and values of a [`Quadtree`].
 ///
@@ -58,14 +64,10 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        match self.handle_iter.next() {
-            Some(handle) => Some(
-                self.store
-                    .get(&handle)
-                    .expect("Shouldn't have an handle in the tree which isn't in the store."),
-            ),
-            None => None,
-        }
+        // There shouldn't be a handle in the tree which isn't in the store, but if there is,
+        // skip over it.
+        let store = self.store;
+        self.handle_iter.find_map(|handle| store.get(&handle))
     }
 
     #[inline]
@@ -76,6 +78,42 @@ where
 
 impl<U, V> FusedIterator for Iter<'_, U, V> where U: PrimInt + Default {}
 
+/// A mutable iterator over all regions and values of a [`Quadtree`].
+///
+/// Only values may be mutated through the returned [`Entry`]; its region is fixed.
+///
+/// This struct is created by the [`iter_mut`] method on [`Quadtree`].
+///
+/// [`iter_mut`]: ../struct.Quadtree.html#method.iter_mut
+/// [`Entry`]: ../entry/struct.Entry.html
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct IterMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: hash_map::ValuesMut<'a, u64, Entry<U, V>>,
+}
+
+impl<'a, U, V> Iterator for IterMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<U, V> FusedIterator for IterMut<'_, U, V> where U: PrimInt + Default {}
+
 /// A consuming iterator over all region/value associations held in a [`Quadtree`].
 ///
 /// This struct is created by the `into_iter()` method on the [`IntoIterator`] trait.
@@ -109,6 +147,39 @@ where
 
 impl<U, V> FusedIterator for IntoIter<U, V> where U: PrimInt + Default {}
 
+/// A draining iterator over all region/value associations held in a [`Quadtree`].
+///
+/// This struct is created by the [`drain`] method on [`Quadtree`].
+///
+/// [`drain`]: ../struct.Quadtree.html#method.drain
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct Drain<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: hash_map::Drain<'a, u64, Entry<U, V>>,
+}
+
+impl<U, V> Iterator for Drain<'_, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, entry)| entry)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<U, V> FusedIterator for Drain<'_, U, V> where U: PrimInt + Default {}
+
 /// An iterator over the regions and values of a [`Quadtree`].
 ///
 /// This struct is created by the [`query`] method on [`Quadtree`].
@@ -130,8 +201,10 @@ impl<'a, U, V> Query<'a, U, V>
 where
     U: PrimInt + Default,
 {
+    // A @query_region of None (i.e. one which couldn't be built) contains nothing, and so
+    // produces an empty query.
     pub(crate) fn new(
-        query_region: impl Into<Area<U>>,
+        query_region: Option<Area<U>>,
         qt: &'a QTInner<U>,
         store: &'a StoreType<U, V>,
         traversal_method: Traversal,
@@ -139,15 +212,21 @@ where
     where
         U: PrimInt + Default,
     {
-        let query_region = query_region.into();
+        let (query_region, handle_iter) = match query_region {
+            Some(query_region) => {
+                // Construct the HandleIter first...
+                let mut handle_iter = HandleIter::new(qt, query_region);
 
-        // Construct the HandleIter first...
-        let mut handle_iter = HandleIter::new(qt, query_region);
+                // ...and descend it to the appropriate level. Depending on the type of
+                // @traversal_method, this will potentially collect intersecting regions along the
+                // way. Avoiding combing the entire Quadtree is essential for the efficiency of a
+                // query.
+                handle_iter.query_optimization(query_region, traversal_method);
 
-        // ...and descend it to the appropriate level. Depending on the type of @traversal_method,
-        // this will potentially collect intersecting regions along the way. Avoiding combing the
-        // entire Quadtree is essential for the efficiency of a query.
-        handle_iter.query_optimization(query_region, traversal_method);
+                (query_region, handle_iter)
+            }
+            None => (qt.region(), HandleIter::empty(qt.region())),
+        };
 
         Query {
             query_region,
@@ -183,6 +262,68 @@ where
 
 impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
 
+/// A mutable iterator over the regions and values of a [`Quadtree`].
+///
+/// This struct is created by the [`query_mut`] method on [`Quadtree`].
+///
+/// [`query_mut`]: ../struct.Quadtree.html#method.query_mut
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct QueryMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    // The handles which matched the query. These are found up front with a regular (immutable)
+    // descent of the tree, since the store can't hand out overlapping mutable borrows by key.
+    handles: HashSet<u64>,
+    inner: IterMut<'a, U, V>,
+}
+
+impl<'a, U, V> QueryMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) fn new(
+        query_region: Option<Area<U>>,
+        qt: &'a QTInner<U>,
+        store: &'a mut StoreType<U, V>,
+        traversal_method: Traversal,
+    ) -> QueryMut<'a, U, V> {
+        let handles = Query::new(query_region, qt, store, traversal_method)
+            .map(|e| e.handle())
+            .collect();
+        QueryMut {
+            handles,
+            inner: IterMut {
+                inner: store.values_mut(),
+            },
+        }
+    }
+}
+
+impl<'a, U, V> Iterator for QueryMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.handles.is_empty() {
+            return None;
+        }
+        let handles = &mut self.handles;
+        self.inner.find(|entry| handles.remove(&entry.handle()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.handles.len()))
+    }
+}
+
+impl<U, V> FusedIterator for QueryMut<'_, U, V> where U: PrimInt + Default {}
+
 /// An iterator over the values held within a [`Quadtree`].
 ///
 /// This struct is created by the [`values`] method on [`Quadtree`].
@@ -216,6 +357,39 @@ where
 
 impl<U, V> FusedIterator for Values<'_, U, V> where U: PrimInt + Default {}
 
+/// A mutable iterator over the values held within a [`Quadtree`].
+///
+/// This struct is created by the [`values_mut`] method on [`Quadtree`].
+///
+/// [`values_mut`]: ../struct.Quadtree.html#method.values_mut
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Debug)]
+pub struct ValuesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) inner: IterMut<'a, U, V>,
+}
+
+impl<'a, U, V> Iterator for ValuesMut<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut V;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|e| e.value_mut())
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<U, V> FusedIterator for ValuesMut<'_, U, V> where U: PrimInt + Default {}
+
 /// An iterator over the regions held within a [`Quadtree`].
 ///
 /// This struct is created by the [`regions`] method on [`Quadtree`].
@@ -248,3 +422,120 @@ where
 }
 
 impl<U, V> FusedIterator for Regions<'_, U, V> where U: PrimInt + Default {}
+
+/// An iterator over all pairs of entries in a [`Quadtree`] whose regions intersect.
+///
+/// This struct is created by the [`intersecting_pairs`] method on [`Quadtree`].
+///
+/// [`intersecting_pairs`]: ../struct.Quadtree.html#method.intersecting_pairs
+/// [`Quadtree`]: ../struct.Quadtree.html
+#[derive(Clone, Debug)]
+pub struct IntersectingPairs<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    store: &'a StoreType<U, V>,
+    // Nodes yet to be visited, alongside the length of @ancestors at the time they were pushed.
+    qt_stack: Vec<(&'a QTInner<U>, usize)>,
+    // The handles held by every node on the path from the root down to (but not including) the
+    // node currently being visited.
+    ancestors: Vec<u64>,
+    // The node currently being visited, if any.
+    current: Option<&'a QTInner<U>>,
+    // Cursors into the current node: @i indexes its handles, @j indexes the candidates which
+    // handles[i] is paired against (the later handles in this node, then the @ancestors).
+    i: usize,
+    j: usize,
+}
+
+impl<'a, U, V> IntersectingPairs<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    pub(crate) fn new(
+        qt: &'a QTInner<U>,
+        store: &'a StoreType<U, V>,
+    ) -> IntersectingPairs<'a, U, V> {
+        IntersectingPairs {
+            store,
+            qt_stack: vec![(qt, 0)],
+            ancestors: vec![],
+            current: None,
+            i: 0,
+            j: 0,
+        }
+    }
+
+    // A handle is replicated into every node it touches, so the same two handles can share a
+    // path through the tree at several nodes. Both entries cover the top-left corner of their
+    // intersection, and each is held by exactly one node on the chain of nodes containing that
+    // corner; we only report the pair from the deeper of those two nodes.
+    fn check(&self, qt: &'a QTInner<U>, a: u64, b: u64) -> Option<<Self as Iterator>::Item> {
+        let (a, b) = (self.store.get(&a)?, self.store.get(&b)?);
+        if !a.area().intersects(b.area()) {
+            return None;
+        }
+        if !qt.region().holds_overlap_corner(a.area(), b.area()) {
+            return None;
+        }
+        if a.handle() < b.handle() {
+            Some((a, b))
+        } else {
+            Some((b, a))
+        }
+    }
+}
+
+impl<'a, U, V> Iterator for IntersectingPairs<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = (&'a Entry<U, V>, &'a Entry<U, V>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(qt) = self.current {
+                let handles = qt.handles();
+                while self.i < handles.len() {
+                    let siblings = handles.len() - self.i - 1;
+                    while self.j < siblings + self.ancestors.len() {
+                        let other = if self.j < siblings {
+                            handles[self.i + 1 + self.j]
+                        } else {
+                            self.ancestors[self.j - siblings]
+                        };
+                        self.j += 1;
+                        if let Some(pair) = self.check(qt, handles[self.i], other) {
+                            return Some(pair);
+                        }
+                    }
+                    self.i += 1;
+                    self.j = 0;
+                }
+
+                // This node is exhausted; its handles become ancestors of its subquadrants.
+                self.ancestors.extend(handles);
+                if let Some(sqs) = qt.subquadrants() {
+                    for sq in sqs {
+                        self.qt_stack.push((sq, self.ancestors.len()));
+                    }
+                }
+                self.current = None;
+            }
+
+            let (qt, depth) = self.qt_stack.pop()?;
+            self.ancestors.truncate(depth);
+            self.current = Some(qt);
+            self.i = 0;
+            self.j = 0;
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<U, V> FusedIterator for IntersectingPairs<'_, U, V> where U: PrimInt + Default {}
@@ -0,0 +1,539 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An operation log for replaying a [`Quadtree`]'s changes, e.g. to recover it after a crash.
+//!
+//! A [`JournaledQuadtree`] wraps a quadtree and a [`Log`], such as a file. Every
+//! [`.insert()`], [`.delete_by_handle()`], [`.modify()`], and [`.reset()`] made through it is
+//! applied to the quadtree and then appended to the log as a record naming the affected handles.
+//! [`Quadtree::replay()`] reads the log back into an identical quadtree, with the same handles.
+//!
+//! The log always begins with a checkpoint: a snapshot of the whole quadtree in the [binary
+//! format]. [`.checkpoint()`] replaces the log with a fresh snapshot, which keeps the log from
+//! growing without bound; [`.checkpoint_every()`] does so automatically after a number of
+//! records.
+//! ```
+//! use quadtree_rs::{journal::JournaledQuadtree, Quadtree};
+//!
+//! let mut jqt = JournaledQuadtree::new(Quadtree::<u8, u32>::new(3), Vec::new()).unwrap();
+//! let a = jqt.insert(((0, 0), (2, 2)), 1).unwrap().unwrap();
+//! let b = jqt.insert((5, 5), 2).unwrap().unwrap();
+//! jqt.modify((1, 1), |v| *v += 10).unwrap();
+//! jqt.delete_by_handle(b).unwrap();
+//!
+//! // Reads are made through the wrapped quadtree.
+//! assert_eq!(jqt.get(a).unwrap().value_ref(), &11);
+//!
+//! let copy = Quadtree::<u8, u32>::replay(jqt.log()).unwrap();
+//! assert_eq!(copy.to_bytes().unwrap(), jqt.to_bytes().unwrap());
+//! ```
+//!
+//! # Layout
+//!
+//! All integers are little-endian, and coordinates and values are written as in the [binary
+//! format].
+//!
+//! | Section | Field        | Encoding                                                 |
+//! |---------|--------------|----------------------------------------------------------|
+//! | Header  | magic        | the four bytes `QTJL`                                    |
+//! |         | version      | `u16`, currently `1`                                     |
+//! | Records | kind         | `u8`                                                     |
+//! |         | length       | `u32`, the length of the payload                         |
+//! |         | payload      | that many bytes, depending on the kind                   |
+//!
+//! | Kind | Record     | Payload                                                          |
+//! |------|------------|------------------------------------------------------------------|
+//! | `0`  | checkpoint | the quadtree, in the binary format                               |
+//! | `1`  | insert     | `u64` handle, four coordinates (`x`, `y`, width, height), value  |
+//! | `2`  | delete     | `u64` handle                                                     |
+//! | `3`  | modify     | `u64` handle, the new value                                      |
+//! | `4`  | reset      | nothing                                                          |
+//!
+//! The first record is always a checkpoint, and no other record is. A [`.modify()`] writes one
+//! record per entry it changed.
+//!
+//! If the process dies partway through appending a record, the log ends with an incomplete
+//! record. [`Quadtree::replay()`] ignores it, so the recovered quadtree reflects every record
+//! which was written in full. Start a new journal from the recovered quadtree, which writes a
+//! fresh checkpoint, rather than appending after the incomplete record.
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`JournaledQuadtree`]: struct.JournaledQuadtree.html
+//! [`Log`]: trait.Log.html
+//! [`.insert()`]: struct.JournaledQuadtree.html#method.insert
+//! [`.delete_by_handle()`]: struct.JournaledQuadtree.html#method.delete_by_handle
+//! [`.modify()`]: struct.JournaledQuadtree.html#method.modify
+//! [`.reset()`]: struct.JournaledQuadtree.html#method.reset
+//! [`.checkpoint()`]: struct.JournaledQuadtree.html#method.checkpoint
+//! [`.checkpoint_every()`]: struct.JournaledQuadtree.html#method.checkpoint_every
+//! [`Quadtree::replay()`]: ../struct.Quadtree.html#method.replay
+//! [binary format]: ../binary/index.html
+
+use crate::{
+    area::Area,
+    binary::{
+        self,
+        Codec,
+        DecodeError,
+    },
+    entry::Entry,
+    point::Point,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::{
+    convert::{
+        TryFrom,
+        TryInto,
+    },
+    default::Default,
+    fmt,
+    fs::File,
+    io::{
+        self,
+        Seek,
+        SeekFrom,
+        Write,
+    },
+    mem,
+    ops::Deref,
+};
+
+const MAGIC: &[u8; 4] = b"QTJL";
+/// The version of the log format written by this crate.
+pub const VERSION: u16 = 1;
+
+const CHECKPOINT: u8 = 0;
+const INSERT: u8 = 1;
+const DELETE: u8 = 2;
+const MODIFY: u8 = 3;
+const RESET: u8 = 4;
+
+// The length of a record's kind and length fields.
+const RECORD_HEADER: usize = 5;
+
+/// Where a [`JournaledQuadtree`] writes its log.
+///
+/// Implemented for `Vec<u8>`, which keeps the log in memory, and for [`File`].
+///
+/// [`JournaledQuadtree`]: struct.JournaledQuadtree.html
+/// [`File`]: https://doc.rust-lang.org/std/fs/struct.File.html
+pub trait Log {
+    /// Appends bytes to the end of the log.
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()>;
+
+    /// Replaces the whole contents of the log with `bytes`.
+    ///
+    /// If this can fail partway through, the log may be left empty or incomplete. An
+    /// implementation which must survive that can write to a temporary file and rename it over
+    /// the log.
+    fn rewrite(&mut self, bytes: &[u8]) -> io::Result<()>;
+}
+
+impl Log for Vec<u8> {
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn rewrite(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.clear();
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// Appends are written at the end of the file, but not synced to disk; call
+/// [`File::sync_data()`] through [`.log()`] for that. Rewrites truncate the file and are synced.
+///
+/// [`File::sync_data()`]: https://doc.rust-lang.org/std/fs/struct.File.html#method.sync_data
+/// [`.log()`]: struct.JournaledQuadtree.html#method.log
+impl Log for File {
+    fn append(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.seek(SeekFrom::End(0))?;
+        self.write_all(bytes)
+    }
+
+    fn rewrite(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.set_len(0)?;
+        self.seek(SeekFrom::Start(0))?;
+        self.write_all(bytes)?;
+        self.sync_data()
+    }
+}
+
+/// The error returned when a log can't be replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReplayError {
+    /// The log doesn't start with the format's magic bytes.
+    BadMagic,
+    /// The log was written in a newer version of the format.
+    UnsupportedVersion(u16),
+    /// The log doesn't start with a complete checkpoint.
+    MissingCheckpoint,
+    /// The checkpoint couldn't be read.
+    Checkpoint(DecodeError),
+    /// The record with this index, counting from the checkpoint at `0`, is malformed.
+    InvalidRecord(usize),
+    /// The record with this index names a handle which doesn't match the quadtree, e.g. a delete
+    /// of an entry which isn't present.
+    HandleMismatch(usize),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::BadMagic => write!(f, "input is not a quadtree log"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "unsupported log version {}", v),
+            ReplayError::MissingCheckpoint => write!(f, "log doesn't start with a checkpoint"),
+            ReplayError::Checkpoint(err) => write!(f, "invalid checkpoint: {}", err),
+            ReplayError::InvalidRecord(i) => write!(f, "invalid record {}", i),
+            ReplayError::HandleMismatch(i) => {
+                write!(f, "record {} doesn't match the quadtree's handles", i)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// A [`Quadtree`] which logs every change made through it. See the [`journal`] module.
+///
+/// Derefs to the wrapped quadtree, so all of its read-only methods are available. Changes must
+/// be made through the methods here to be logged.
+///
+/// Each change is applied to the quadtree before its record is written. If writing fails, the
+/// error is returned, but the change stays applied and the log no longer matches the quadtree;
+/// a successful [`.checkpoint()`] brings them back in line. A record whose payload is 4GiB or
+/// longer can't be written, and fails with an error of kind [`InvalidInput`].
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`journal`]: index.html
+/// [`.checkpoint()`]: #method.checkpoint
+/// [`InvalidInput`]: https://doc.rust-lang.org/std/io/enum.ErrorKind.html#variant.InvalidInput
+pub struct JournaledQuadtree<U, V, L>
+where
+    U: PrimInt + Default,
+{
+    qt: Quadtree<U, V>,
+    log: L,
+    interval: usize,
+    records: usize,
+}
+
+impl<U, V, L> JournaledQuadtree<U, V, L>
+where
+    U: PrimInt + Default,
+    V: Codec,
+    L: Log,
+{
+    /// Starts a journal of `qt`, replacing the contents of `log` with a checkpoint of it.
+    pub fn new(qt: Quadtree<U, V>, mut log: L) -> io::Result<Self> {
+        log.rewrite(&checkpoint(&qt)?)?;
+        Ok(JournaledQuadtree {
+            qt,
+            log,
+            interval: 0,
+            records: 0,
+        })
+    }
+
+    /// Takes a checkpoint automatically whenever `records` records have been written since the
+    /// last one. `0`, the default, turns this off.
+    pub fn checkpoint_every(mut self, records: usize) -> Self {
+        self.interval = records;
+        self
+    }
+
+    /// Replaces the log with a checkpoint of the quadtree as it is now.
+    pub fn checkpoint(&mut self) -> io::Result<()> {
+        self.log.rewrite(&checkpoint(&self.qt)?)?;
+        self.records = 0;
+        Ok(())
+    }
+
+    /// The number of records written since the last checkpoint.
+    pub fn records_since_checkpoint(&self) -> usize {
+        self.records
+    }
+
+    /// The log.
+    pub fn log(&self) -> &L {
+        &self.log
+    }
+
+    /// Stops journaling, and returns the quadtree and the log.
+    pub fn into_inner(self) -> (Quadtree<U, V>, L) {
+        (self.qt, self.log)
+    }
+
+    /// Logs a [`Quadtree::insert()`]. Nothing is logged if the insertion fails.
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: impl TryInto<Area<U>>, val: V) -> io::Result<Option<u64>> {
+        let handle = match self.qt.insert(region, val) {
+            Some(handle) => handle,
+            None => return Ok(None),
+        };
+        let mut out = Vec::new();
+        if let Some(entry) = self.qt.get(handle) {
+            let mut payload = handle.to_le_bytes().to_vec();
+            let anchor = entry.anchor();
+            for n in &[anchor.x, anchor.y, entry.width(), entry.height()] {
+                binary::write_coord(&mut payload, *n);
+            }
+            entry.value_ref().encode(&mut payload);
+            write_record(&mut out, INSERT, &payload)?;
+        }
+        self.write(&out, 1)?;
+        Ok(Some(handle))
+    }
+
+    /// Logs a [`Quadtree::delete_by_handle()`]. Nothing is logged if there's no such entry.
+    ///
+    /// [`Quadtree::delete_by_handle()`]: ../struct.Quadtree.html#method.delete_by_handle
+    pub fn delete_by_handle(&mut self, handle: u64) -> io::Result<Option<Entry<U, V>>> {
+        let entry = match self.qt.delete_by_handle(handle) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        let mut out = Vec::new();
+        write_record(&mut out, DELETE, &handle.to_le_bytes())?;
+        self.write(&out, 1)?;
+        Ok(Some(entry))
+    }
+
+    /// Logs a [`Quadtree::modify()`], as the new value of each entry in the area.
+    ///
+    /// [`Quadtree::modify()`]: ../struct.Quadtree.html#method.modify
+    pub fn modify<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&mut V),
+    {
+        let mut out = Vec::new();
+        let mut count = 0;
+        let mut encoded = Ok(());
+        for entry in self.qt.query_mut(area) {
+            f(entry.value_mut());
+            // Every entry is modified, even once a record has failed.
+            if encoded.is_ok() {
+                let mut payload = entry.handle().to_le_bytes().to_vec();
+                entry.value_ref().encode(&mut payload);
+                encoded = write_record(&mut out, MODIFY, &payload);
+            }
+            count += 1;
+        }
+        encoded?;
+        self.write(&out, count)
+    }
+
+    /// Logs a [`Quadtree::reset()`].
+    ///
+    /// [`Quadtree::reset()`]: ../struct.Quadtree.html#method.reset
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.qt.reset();
+        let mut out = Vec::new();
+        write_record(&mut out, RESET, &[])?;
+        self.write(&out, 1)
+    }
+
+    // fn
+
+    // Appends @count records, already encoded in @bytes, or takes a checkpoint instead if one is
+    // due. The checkpoint covers the records, so they needn't be written too.
+    fn write(&mut self, bytes: &[u8], count: usize) -> io::Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+        if self.interval > 0 && self.records + count >= self.interval {
+            return self.checkpoint();
+        }
+        self.log.append(bytes)?;
+        self.records += count;
+        Ok(())
+    }
+}
+
+impl<U, V, L> Deref for JournaledQuadtree<U, V, L>
+where
+    U: PrimInt + Default,
+{
+    type Target = Quadtree<U, V>;
+
+    fn deref(&self) -> &Quadtree<U, V> {
+        &self.qt
+    }
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Codec,
+{
+    /// Rebuilds a quadtree from a log written by a [`JournaledQuadtree`], with the same handles
+    /// and values. See the [`journal`] module.
+    ///
+    /// An incomplete record at the end of the log is ignored.
+    /// ```
+    /// use quadtree_rs::{journal::{JournaledQuadtree, ReplayError}, Quadtree};
+    ///
+    /// let mut jqt = JournaledQuadtree::new(Quadtree::<u8, u8>::new(2), Vec::new()).unwrap();
+    /// jqt.insert((0, 0), 1).unwrap();
+    /// jqt.insert((1, 1), 2).unwrap();
+    /// let log = jqt.log();
+    ///
+    /// // Losing the end of the last record loses only that insertion.
+    /// let qt = Quadtree::<u8, u8>::replay(&log[..log.len() - 1]).unwrap();
+    /// assert_eq!(qt.len(), 1);
+    ///
+    /// assert_eq!(Quadtree::<u8, u8>::replay(b"QTRS"), Err(ReplayError::BadMagic));
+    /// ```
+    ///
+    /// [`JournaledQuadtree`]: journal/struct.JournaledQuadtree.html
+    /// [`journal`]: journal/index.html
+    pub fn replay(log: &[u8]) -> Result<Self, ReplayError> {
+        if log.len() < MAGIC.len() + 2 || &log[..MAGIC.len()] != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = u16::from_le_bytes([log[4], log[5]]);
+        if version > VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+
+        let mut records = Records {
+            bytes: &log[MAGIC.len() + 2..],
+        };
+        let mut qt = match records.next() {
+            Some((CHECKPOINT, payload)) => {
+                Quadtree::from_bytes(payload).map_err(ReplayError::Checkpoint)?
+            }
+            _ => return Err(ReplayError::MissingCheckpoint),
+        };
+        for (i, (kind, payload)) in records.enumerate() {
+            apply(&mut qt, i + 1, kind, payload)?;
+        }
+        Ok(qt)
+    }
+}
+
+// The log header and a checkpoint record of @qt.
+fn checkpoint<U, V>(qt: &Quadtree<U, V>) -> io::Result<Vec<u8>>
+where
+    U: PrimInt + Default,
+    V: Codec,
+{
+    let mut out = MAGIC.to_vec();
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    let bytes = qt
+        .to_bytes()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    write_record(&mut out, CHECKPOINT, &bytes)?;
+    Ok(out)
+}
+
+// Appends a record of @kind to @out. Fails, leaving @out as it was, if @payload is too long
+// for its length to fit in a u32.
+fn write_record(out: &mut Vec<u8>, kind: u8, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "record payload is 4GiB or longer",
+        )
+    })?;
+    out.push(kind);
+    out.extend_from_slice(&len.to_le_bytes());
+    out.extend_from_slice(payload);
+    Ok(())
+}
+
+// Applies record @i, of @kind, to @qt.
+fn apply<U, V>(
+    qt: &mut Quadtree<U, V>,
+    i: usize,
+    kind: u8,
+    payload: &[u8],
+) -> Result<(), ReplayError>
+where
+    U: PrimInt + Default,
+    V: Codec,
+{
+    let invalid = ReplayError::InvalidRecord(i);
+    let width = mem::size_of::<U>();
+    let handle = |payload: &[u8]| -> Result<u64, ReplayError> {
+        payload
+            .get(..8)
+            .and_then(u64::decode)
+            .ok_or(ReplayError::InvalidRecord(i))
+    };
+
+    match kind {
+        INSERT => {
+            let handle = handle(payload)?;
+            let coord = |n: usize| {
+                payload
+                    .get(8 + n * width..8 + (n + 1) * width)
+                    .and_then(binary::read_coord::<U>)
+                    .ok_or(invalid)
+            };
+            let anchor = Point {
+                x: coord(0)?,
+                y: coord(1)?,
+            };
+            let region = Area::new(anchor, (coord(2)?, coord(3)?)).map_err(|_| invalid)?;
+            let val = V::decode(&payload[8 + 4 * width..]).ok_or(invalid)?;
+            if qt.inner().handle_counter() != handle {
+                return Err(ReplayError::HandleMismatch(i));
+            }
+            qt.insert(region, val).ok_or(invalid)?;
+        }
+        DELETE => {
+            if payload.len() != 8 {
+                return Err(invalid);
+            }
+            qt.delete_by_handle(handle(payload)?)
+                .ok_or(ReplayError::HandleMismatch(i))?;
+        }
+        MODIFY => {
+            let handle = handle(payload)?;
+            let val = V::decode(&payload[8..]).ok_or(invalid)?;
+            *qt.get_mut(handle)
+                .ok_or(ReplayError::HandleMismatch(i))?
+                .value_mut() = val;
+        }
+        RESET if payload.is_empty() => qt.reset(),
+        _ => return Err(invalid),
+    }
+    Ok(())
+}
+
+// Splits the records of a log, after its header, into their kinds and payloads. Stops at the
+// first incomplete record.
+struct Records<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = (u8, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let header = self.bytes.get(..RECORD_HEADER)?;
+        let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+        let payload = self
+            .bytes
+            .get(RECORD_HEADER..RECORD_HEADER.checked_add(len)?)?;
+        self.bytes = &self.bytes[RECORD_HEADER + len..];
+        Some((header[0], payload))
+    }
+}
@@ -26,11 +26,11 @@
 //! assert_eq!(qt.width(), 16);
 //!
 //! // Associate the value "foo" with a rectangle of size 2x1, anchored at (0, 0).
-//! let region_a: Area<u64> = ((0,0),(2,1)).into();
+//! let region_a: Area<u64> = Area::new((0,0), (2,1)).unwrap();
 //! qt.insert(region_a, "foo".to_string());
 //!
 //! // Query over a region of size 2x2, anchored at (1, 0).
-//! let region_b: Area<u64> = ((1,0),(2,2)).into();
+//! let region_b: Area<u64> = Area::new((1,0), (2,2)).unwrap();
 //! let mut query = qt.query(region_b);
 //!
 //! // The query region (region_b) intersects the region "foo" is associated with (region_a), so the query iterator returns "foo" by reference.
@@ -59,7 +59,7 @@
 //! //                           +---+---+---+---+
 //!
 //! // Often inserting a large region requires traversing only as far down as necessary to fully cover that region.
-//! let region_b: Area<u8> = ((0,0),(2,2)).into();
+//! let region_b: Area<u8> = Area::new((0,0), (2,2)).unwrap();
 //! qt.insert(region_b, 'b');
 //!
 //! // (0,0)->4x4                +---+---+---+---+
@@ -73,7 +73,7 @@
 //! //                           +---+---+---+---+
 //!
 //! // If a region cannot be represented by one node in the tree, a handle type is inserted in multiple places.
-//! let region_c: Area<u8> = ((0,0),(3,3)).into();
+//! let region_c: Area<u8> = Area::new((0,0), (3,3)).unwrap();
 //! qt.insert(region_c, 'c');
 //!
 //! // (0,0)->4x4                +---+---+---+---+
@@ -102,9 +102,27 @@
 #![doc(test(attr(deny(warnings))))]
 
 pub mod area;
+pub mod binary;
+pub mod concurrent;
+pub mod csv;
+pub mod dot;
 pub mod entry;
+pub mod error;
+pub mod frozen;
+#[cfg(feature = "geojson")]
+pub mod geojson;
 pub mod iter;
+pub mod journal;
+#[cfg(feature = "rayon")]
+pub mod par;
+pub mod persistent;
 pub mod point;
+#[cfg(feature = "serde")]
+pub mod serde_entries;
+pub mod svg;
+pub mod text;
+pub mod watch;
+pub mod wkt;
 
 mod handle_iter;
 mod qtinner;
@@ -116,5 +134,9 @@ pub use area::{
     Area,
     AreaBuilder,
 };
+pub use concurrent::ConcurrentQuadtree;
+pub use error::Error;
+pub use persistent::PersistentQuadtree;
 pub use point::Point;
 pub use quadtree::Quadtree;
+pub use traversal::Traversal;
@@ -0,0 +1,207 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parallel scans and bulk operations on a [`Quadtree`], with
+//! [rayon](https://docs.rs/rayon). Requires the `rayon` feature.
+//!
+//!   - [`.par_iter()`] and [`.par_modify_all()`] split the entries across rayon's thread pool;
+//!   - [`.par_query()`] and [`.par_query_strict()`] search the four subquadrants of each node
+//!     in parallel;
+//!   - [`.par_insert_all()`] inserts many entries at once, filing them into the four
+//!     subquadrants of each node in parallel.
+//! ```
+//! use quadtree_rs::Quadtree;
+//! use rayon::prelude::*;
+//!
+//! let mut qt = Quadtree::<u32, u64>::new(8);
+//! let handles = qt.par_insert_all((0..256).map(|i| ((i, i), u64::from(i))));
+//! assert_eq!(handles.len(), 256);
+//!
+//! qt.par_modify_all(|v| *v *= 2);
+//! let sum: u64 = qt.par_query(((0, 0), (4, 4))).map(|e| *e.value_ref()).sum();
+//! assert_eq!(sum, 2 * (0 + 1 + 2 + 3));
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`.par_iter()`]: ../struct.Quadtree.html#method.par_iter
+//! [`.par_modify_all()`]: ../struct.Quadtree.html#method.par_modify_all
+//! [`.par_query()`]: ../struct.Quadtree.html#method.par_query
+//! [`.par_query_strict()`]: ../struct.Quadtree.html#method.par_query_strict
+//! [`.par_insert_all()`]: ../struct.Quadtree.html#method.par_insert_all
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    traversal::Traversal,
+    types::StoreType,
+};
+use num::PrimInt;
+use rayon::prelude::*;
+use std::{
+    convert::TryInto,
+    default::Default,
+};
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default + Send + Sync,
+{
+    /// A parallel variant of [`.iter()`], over all region/value associations in the quadtree,
+    /// in no particular order.
+    ///
+    /// [`.iter()`]: #method.iter
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = &Entry<U, V>>
+    where
+        V: Sync,
+    {
+        self.store().par_iter().map(|(_, entry)| entry)
+    }
+
+    /// A parallel variant of [`.query()`], over all region/value associations which intersect
+    /// the given region. See the [`par`] module.
+    ///
+    /// The matching entries are found up front, with the subquadrants of each node searched in
+    /// parallel.
+    ///
+    /// [`.query()`]: #method.query
+    /// [`par`]: par/index.html
+    pub fn par_query(
+        &self,
+        area: impl TryInto<Area<U>>,
+    ) -> impl ParallelIterator<Item = &Entry<U, V>>
+    where
+        V: Sync,
+    {
+        self.par_query_by(area, Traversal::Overlapping)
+    }
+
+    /// A parallel variant of [`.query_strict()`]. See [`.par_query()`].
+    ///
+    /// [`.query_strict()`]: #method.query_strict
+    /// [`.par_query()`]: #method.par_query
+    pub fn par_query_strict(
+        &self,
+        area: impl TryInto<Area<U>>,
+    ) -> impl ParallelIterator<Item = &Entry<U, V>>
+    where
+        V: Sync,
+    {
+        self.par_query_by(area, Traversal::Strict)
+    }
+
+    /// A parallel variant of [`.modify_all()`].
+    ///
+    /// [`.modify_all()`]: #method.modify_all
+    pub fn par_modify_all<F>(&mut self, f: F)
+    where
+        V: Send,
+        F: Fn(&mut V) + Sync + Send,
+    {
+        self.parts_mut()
+            .1
+            .par_iter_mut()
+            .for_each(|(_, entry)| f(entry.value_mut()));
+    }
+
+    /// Inserts every region/value pair, and returns what [`.insert()`] would have returned for
+    /// each, in order. See the [`par`] module.
+    ///
+    /// The result, including every handle, is the same as inserting the pairs one by one. Each
+    /// node's share of the regions is filed into its four subquadrants in parallel, so this is
+    /// suited to building a large tree in bulk.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u8, char>::new(2);
+    /// let handles = qt.par_insert_all(vec![((0, 0), 'a'), ((9, 9), 'b'), ((1, 1), 'c')]);
+    /// assert_eq!(handles, vec![Some(0), None, Some(1)]);
+    /// ```
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`par`]: par/index.html
+    pub fn par_insert_all<A, I>(&mut self, entries: I) -> Vec<Option<u64>>
+    where
+        A: TryInto<Area<U>>,
+        I: IntoIterator<Item = (A, V)>,
+    {
+        let (inner, store) = self.parts_mut();
+        let mut reqs = Vec::new();
+        let handles = entries
+            .into_iter()
+            .map(|(region, val)| {
+                let region = region
+                    .try_into()
+                    .ok()
+                    .filter(|region| inner.region().contains(*region))?;
+                let handle = inner.next_handle();
+                store.insert(handle, Entry::new((region, val), handle));
+                reqs.push((region, handle));
+                Some(handle)
+            })
+            .collect();
+        inner.par_insert_handles_at_regions(&reqs);
+        handles
+    }
+
+    // fn
+
+    fn par_query_by(
+        &self,
+        area: impl TryInto<Area<U>>,
+        traversal: Traversal,
+    ) -> impl ParallelIterator<Item = &Entry<U, V>>
+    where
+        V: Sync,
+    {
+        let found = match area.try_into() {
+            Ok(area) => query_node(self.inner(), self.store(), area, traversal),
+            Err(_) => Vec::new(),
+        };
+        found.into_par_iter()
+    }
+}
+
+// The entries held by @qt and its subquadrants which @traversal selects for @area.
+fn query_node<'a, U, V>(
+    qt: &QTInner<U>,
+    store: &'a StoreType<U, V>,
+    area: Area<U>,
+    traversal: Traversal,
+) -> Vec<&'a Entry<U, V>>
+where
+    U: PrimInt + Default + Send + Sync,
+    V: Sync,
+{
+    let region = qt.region();
+    let mut found: Vec<&Entry<U, V>> = qt
+        .handles()
+        .iter()
+        .filter_map(|handle| store.get(handle))
+        .filter(|entry| {
+            traversal.eval(entry.area(), area) && region.holds_overlap_corner(entry.area(), area)
+        })
+        .collect();
+
+    if let Some(sqs) = qt.subquadrants() {
+        let below: Vec<Vec<&Entry<U, V>>> = sqs
+            .par_iter()
+            .filter(|sq| sq.region().intersects(area))
+            .map(|sq| query_node(sq, store, area, traversal))
+            .collect();
+        found.extend(below.into_iter().flatten());
+    }
+    found
+}
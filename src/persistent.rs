@@ -0,0 +1,610 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A quadtree whose snapshots are cheap to take and to keep around.
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    handle_iter::HandleIter,
+    iter::IntoIter,
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    traversal::Traversal,
+    types::StoreType,
+};
+use num::PrimInt;
+use std::{
+    convert::TryInto,
+    default::Default,
+    fmt::Debug,
+    iter::FusedIterator,
+    sync::Arc,
+};
+
+/// A persistent variant of [`Quadtree`], for taking a snapshot of the tree (say, once per frame)
+/// and reading from it while the original keeps changing.
+///
+/// Nodes and entries are reference-counted and shared between a `PersistentQuadtree` and its
+/// snapshots. Taking a [`.snapshot()`] is O(1). A later mutation of either copies only the nodes
+/// along the path it touches, and only the entries it writes to, leaving everything else shared.
+///
+/// Mutations require `V: Clone`, since a value shared with a snapshot is cloned before it's
+/// written to. Handles are the same as in [`Quadtree`], and are preserved when converting to and
+/// from one.
+/// ```
+/// use quadtree_rs::PersistentQuadtree;
+///
+/// let mut qt = PersistentQuadtree::<u32, String>::new(4);
+/// let handle = qt.insert((1, 1), "before".to_string()).unwrap();
+///
+/// let snapshot = qt.snapshot();
+/// qt.get_mut(handle).unwrap().value_mut().replace_range(.., "after");
+/// qt.insert((9, 9), "new".to_string());
+///
+/// assert_eq!(qt.get(handle).unwrap().value_ref(), "after");
+/// assert_eq!(snapshot.get(handle).unwrap().value_ref(), "before");
+/// assert_eq!(qt.len(), 2);
+/// assert_eq!(snapshot.len(), 1);
+/// ```
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`.snapshot()`]: #method.snapshot
+pub struct PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    inner: Arc<QTInner<U>>,
+    store: HandleTable<U, V>,
+}
+
+impl<U, V> PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Creates a new, empty quadtree with some depth. See [`Quadtree::new()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^depth` can't be represented by `U`. See [`.try_new()`] for a fallible
+    /// variant.
+    ///
+    /// [`Quadtree::new()`]: ../struct.Quadtree.html#method.new
+    /// [`.try_new()`]: #method.try_new
+    pub fn new(depth: usize) -> Self {
+        Self::new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// Creates a new, empty quadtree with some depth and an explicit anchor. See
+    /// [`Quadtree::new_with_anchor()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the region can't be represented by `U`. See [`.try_new_with_anchor()`] for a
+    /// fallible variant.
+    ///
+    /// [`Quadtree::new_with_anchor()`]: ../struct.Quadtree.html#method.new_with_anchor
+    /// [`.try_new_with_anchor()`]: #method.try_new_with_anchor
+    pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
+        Self::try_new_with_anchor(anchor, depth)
+            .expect("The quadtree's region can't be represented by its coordinate type.")
+    }
+
+    /// A fallible variant of [`.new()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth` can't be represented by `U`.
+    ///
+    /// [`.new()`]: #method.new
+    /// [`Error::Overflow`]: ../error/enum.Error.html#variant.Overflow
+    pub fn try_new(depth: usize) -> Result<Self, Error> {
+        Self::try_new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// A fallible variant of [`.new_with_anchor()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth`, or the bottom or right edge of the region, can't
+    /// be represented by `U`.
+    ///
+    /// [`.new_with_anchor()`]: #method.new_with_anchor
+    /// [`Error::Overflow`]: ../error/enum.Error.html#variant.Overflow
+    pub fn try_new_with_anchor(anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: Arc::new(QTInner::try_new(anchor, depth)?),
+            store: HandleTable::new(),
+        })
+    }
+
+    /// Takes a snapshot of this quadtree in O(1).
+    ///
+    /// The snapshot and the original share all of their nodes and entries until one of them is
+    /// mutated. This is equivalent to `.clone()`.
+    pub fn snapshot(&self) -> Self {
+        self.clone()
+    }
+
+    /// The top-left corner (anchor) of the region which this quadtree represents.
+    pub fn anchor(&self) -> Point<U> {
+        self.inner.region().anchor()
+    }
+
+    /// The depth of the quadtree.
+    pub fn depth(&self) -> usize {
+        self.inner.depth()
+    }
+
+    /// The number of elements in the quadtree.
+    pub fn len(&self) -> usize {
+        self.store.len
+    }
+
+    /// Whether or not the quadtree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether or not the region represented by this quadtree could contain the given region.
+    pub fn contains(&self, area: impl TryInto<Area<U>>) -> bool {
+        self.inner.region().contains(area)
+    }
+
+    /// Returns the region/value association with the given handle, if there is one.
+    pub fn get(&self, handle: u64) -> Option<&Entry<U, V>> {
+        self.store.get(handle)
+    }
+
+    /// Returns an iterator over all region/value associations which intersect the given region.
+    /// See [`Quadtree::query()`].
+    ///
+    /// [`Quadtree::query()`]: ../struct.Quadtree.html#method.query
+    pub fn query(&self, area: impl TryInto<Area<U>>) -> Query<'_, U, V> {
+        Query::new(area.try_into().ok(), self, Traversal::Overlapping)
+    }
+
+    /// Returns an iterator over all region/value associations which are contained by the given
+    /// region. See [`Quadtree::query_strict()`].
+    ///
+    /// [`Quadtree::query_strict()`]: ../struct.Quadtree.html#method.query_strict
+    pub fn query_strict(&self, area: impl TryInto<Area<U>>) -> Query<'_, U, V> {
+        Query::new(area.try_into().ok(), self, Traversal::Strict)
+    }
+
+    /// Returns an iterator over all region/value associations in the quadtree, in handle order.
+    pub fn iter(&self) -> Iter<'_, U, V> {
+        Iter {
+            stack: vec![(&*self.store.root, 0)],
+        }
+    }
+}
+
+impl<U, V> PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    // pub
+
+    /// Associates the value with the given region, and returns its handle. See
+    /// [`Quadtree::insert()`].
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: impl TryInto<Area<U>>, val: V) -> Option<u64> {
+        let region = region.try_into().ok()?;
+        if !self.contains(region) {
+            return None;
+        }
+        let inner = Arc::make_mut(&mut self.inner);
+        let handle = inner.next_handle();
+        inner.insert_handle_at_region(region, handle);
+        self.store.insert(Entry::new((region, val), handle));
+        Some(handle)
+    }
+
+    /// Returns a mutable reference to the region/value association with the given handle, if
+    /// there is one. If the value is shared with a snapshot, it's cloned first.
+    pub fn get_mut(&mut self, handle: u64) -> Option<&mut Entry<U, V>> {
+        self.store.get_mut(handle)
+    }
+
+    /// Applies the function to every value which intersects the given region. Only the values
+    /// it's applied to are cloned away from any snapshots.
+    pub fn modify<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F)
+    where
+        F: FnMut(&mut V),
+    {
+        let handles: Vec<u64> = self.query(area).map(|e| e.handle()).collect();
+        for handle in handles {
+            if let Some(entry) = self.store.get_mut(handle) {
+                f(entry.value_mut());
+            }
+        }
+    }
+
+    /// Deletes the region/value association with the given handle, and returns it.
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<Entry<U, V>> {
+        let entry = self.store.remove(handle)?;
+        Arc::make_mut(&mut self.inner).delete_by_handle(handle, entry.area());
+        Some(Arc::try_unwrap(entry).unwrap_or_else(|shared| (*shared).clone()))
+    }
+
+    /// Deletes all region/value associations which intersect the given region, and returns them.
+    /// See [`Quadtree::delete()`].
+    ///
+    /// [`Quadtree::delete()`]: ../struct.Quadtree.html#method.delete
+    pub fn delete(&mut self, area: impl TryInto<Area<U>>) -> IntoIter<U, V> {
+        let handles: Vec<u64> = self.query(area).map(|e| e.handle()).collect();
+        IntoIter {
+            entries: handles
+                .into_iter()
+                .filter_map(|handle| self.delete_by_handle(handle))
+                .collect(),
+        }
+    }
+}
+
+impl<U, V> Clone for PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<U, V> Debug for PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default + Debug,
+    V: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PersistentQuadtree")
+            .field("inner", &self.inner)
+            .field("store", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Converts a [`Quadtree`] in O(n), keeping every handle.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+impl<U, V> From<Quadtree<U, V>> for PersistentQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn from(qt: Quadtree<U, V>) -> Self {
+        let (inner, store) = qt.into_parts();
+        let mut table = HandleTable::new();
+        for (_, entry) in store {
+            table.insert(entry);
+        }
+        Self {
+            inner: Arc::new(inner),
+            store: table,
+        }
+    }
+}
+
+/// Converts back to a [`Quadtree`] in O(n), keeping every handle. Values still shared with a
+/// snapshot are cloned.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+impl<U, V> From<PersistentQuadtree<U, V>> for Quadtree<U, V>
+where
+    U: PrimInt + Default,
+    V: Clone,
+{
+    fn from(qt: PersistentQuadtree<U, V>) -> Self {
+        let store: StoreType<U, V> = qt
+            .iter()
+            .map(|entry| (entry.handle(), entry.clone()))
+            .collect();
+        let inner = Arc::try_unwrap(qt.inner).unwrap_or_else(|shared| (*shared).clone());
+        Quadtree::from_parts(inner, store)
+    }
+}
+
+/// An iterator over the region/value associations of a [`PersistentQuadtree`] which intersect,
+/// or are contained by, some region.
+///
+/// This struct is created by the [`query`] and [`query_strict`] methods on
+/// [`PersistentQuadtree`].
+///
+/// [`query`]: struct.PersistentQuadtree.html#method.query
+/// [`query_strict`]: struct.PersistentQuadtree.html#method.query_strict
+/// [`PersistentQuadtree`]: struct.PersistentQuadtree.html
+pub struct Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    query_region: Area<U>,
+    handle_iter: HandleIter<'a, U>,
+    store: &'a HandleTable<U, V>,
+    traversal_method: Traversal,
+}
+
+impl<'a, U, V> Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    // A @query_region of None (i.e. one which couldn't be built) produces an empty query.
+    fn new(
+        query_region: Option<Area<U>>,
+        qt: &'a PersistentQuadtree<U, V>,
+        traversal_method: Traversal,
+    ) -> Self {
+        let (query_region, handle_iter) = match query_region {
+            Some(query_region) => {
+                let mut handle_iter = HandleIter::new(&qt.inner, query_region);
+                handle_iter.query_optimization(query_region, traversal_method);
+                (query_region, handle_iter)
+            }
+            None => (qt.inner.region(), HandleIter::empty(qt.inner.region())),
+        };
+        Query {
+            query_region,
+            handle_iter,
+            store: &qt.store,
+            traversal_method,
+        }
+    }
+}
+
+impl<'a, U, V> Iterator for Query<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        for handle in self.handle_iter.by_ref() {
+            if let Some(entry) = self.store.get(handle) {
+                if self.traversal_method.eval(entry.area(), self.query_region) {
+                    return Some(entry);
+                }
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.store.len))
+    }
+}
+
+impl<U, V> FusedIterator for Query<'_, U, V> where U: PrimInt + Default {}
+
+/// An iterator over all region/value associations in a [`PersistentQuadtree`], in handle order.
+///
+/// This struct is created by the [`iter`] method on [`PersistentQuadtree`].
+///
+/// [`iter`]: struct.PersistentQuadtree.html#method.iter
+/// [`PersistentQuadtree`]: struct.PersistentQuadtree.html
+pub struct Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    // The nodes on the path to the next entry, and the next slot to visit in each.
+    stack: Vec<(&'a Node<U, V>, usize)>,
+}
+
+impl<'a, U, V> Iterator for Iter<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a Entry<U, V>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, i)) = self.stack.pop() {
+            if i == WIDTH {
+                continue;
+            }
+            self.stack.push((node, i + 1));
+            match node {
+                Node::Branch(children) => {
+                    if let Some(child) = children[i].as_deref() {
+                        self.stack.push((child, 0));
+                    }
+                }
+                Node::Leaf(entries) => {
+                    if let Some(entry) = entries[i].as_deref() {
+                        return Some(entry);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<U, V> FusedIterator for Iter<'_, U, V> where U: PrimInt + Default {}
+
+// The number of bits of a handle consumed at each level of a HandleTable.
+const BITS: u32 = 4;
+const WIDTH: usize = 1 << BITS;
+
+// A persistent map from handles to entries, laid out as a trie over the bits of each handle.
+// Handles are minted from a counter, so the trie stays dense. Nodes and entries are shared
+// between clones of the table and copied on write, so a write duplicates only the nodes along
+// its path and the entry it touches.
+struct HandleTable<U, V>
+where
+    U: PrimInt + Default,
+{
+    root: Arc<Node<U, V>>,
+    // The number of branch levels above the leaves.
+    height: u32,
+    len: usize,
+}
+
+enum Node<U, V>
+where
+    U: PrimInt + Default,
+{
+    Branch([Option<Arc<Node<U, V>>>; WIDTH]),
+    Leaf([Option<Arc<Entry<U, V>>>; WIDTH]),
+}
+
+impl<U, V> HandleTable<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn new() -> Self {
+        Self {
+            root: Arc::new(Node::empty(0)),
+            height: 0,
+            len: 0,
+        }
+    }
+
+    fn get(&self, handle: u64) -> Option<&Entry<U, V>> {
+        if !self.fits(handle) {
+            return None;
+        }
+        let mut node = &*self.root;
+        let mut level = self.height;
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = children[Self::slot(handle, level)].as_deref()?;
+                    level -= 1;
+                }
+                Node::Leaf(entries) => return entries[Self::slot(handle, 0)].as_deref(),
+            }
+        }
+    }
+
+    fn get_mut(&mut self, handle: u64) -> Option<&mut Entry<U, V>>
+    where
+        V: Clone,
+    {
+        // Check first, so that looking up a missing handle doesn't build out a path to it.
+        self.get(handle)?;
+        self.slot_mut(handle).as_mut().map(Arc::make_mut)
+    }
+
+    // Files @entry under its own handle, replacing whatever was there.
+    fn insert(&mut self, entry: Entry<U, V>) {
+        let handle = entry.handle();
+        while !self.fits(handle) {
+            let old_root = std::mem::replace(&mut self.root, Arc::new(Node::empty(1)));
+            if let Some(Node::Branch(children)) = Arc::get_mut(&mut self.root) {
+                children[0] = Some(old_root);
+            }
+            self.height += 1;
+        }
+        if self.slot_mut(handle).replace(Arc::new(entry)).is_none() {
+            self.len += 1;
+        }
+    }
+
+    fn remove(&mut self, handle: u64) -> Option<Arc<Entry<U, V>>> {
+        self.get(handle)?;
+        let entry = self.slot_mut(handle).take();
+        self.len -= 1;
+        entry
+    }
+
+    fn fits(&self, handle: u64) -> bool {
+        handle.checked_shr(BITS * (self.height + 1)).unwrap_or(0) == 0
+    }
+
+    // The slot for @handle in a node at @level, where the leaves are at level zero.
+    fn slot(handle: u64, level: u32) -> usize {
+        (handle.checked_shr(BITS * level).unwrap_or(0) as usize) & (WIDTH - 1)
+    }
+
+    // The leaf slot for @handle, copying every shared node on the way down and building out any
+    // which are missing. Expects @handle to fit.
+    fn slot_mut(&mut self, handle: u64) -> &mut Option<Arc<Entry<U, V>>> {
+        fn descend<U, V>(
+            node: &mut Node<U, V>,
+            handle: u64,
+            level: u32,
+        ) -> &mut Option<Arc<Entry<U, V>>>
+        where
+            U: PrimInt + Default,
+        {
+            match node {
+                Node::Branch(children) => {
+                    let child = children[HandleTable::<U, V>::slot(handle, level)]
+                        .get_or_insert_with(|| Arc::new(Node::empty(level - 1)));
+                    descend(Arc::make_mut(child), handle, level - 1)
+                }
+                Node::Leaf(entries) => &mut entries[HandleTable::<U, V>::slot(handle, 0)],
+            }
+        }
+        descend(Arc::make_mut(&mut self.root), handle, self.height)
+    }
+}
+
+impl<U, V> Clone for HandleTable<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        Self {
+            root: Arc::clone(&self.root),
+            height: self.height,
+            len: self.len,
+        }
+    }
+}
+
+impl<U, V> Node<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn empty(level: u32) -> Self {
+        if level == 0 {
+            Node::Leaf(Default::default())
+        } else {
+            Node::Branch(Default::default())
+        }
+    }
+}
+
+// A shallow copy: children and entries are shared, not cloned. Hand-rolled, since deriving would
+// require `V: Clone`.
+impl<U, V> Clone for Node<U, V>
+where
+    U: PrimInt + Default,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Node::Branch(children) => Node::Branch(children.clone()),
+            Node::Leaf(entries) => Node::Leaf(entries.clone()),
+        }
+    }
+}
@@ -15,10 +15,13 @@
 use crate::{
     area::Area,
     entry::Entry,
+    error::Error,
     point::Point,
     types::StoreType,
 };
 use num::PrimInt;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 #[cfg(feature = "serde")]
 use serde::{
     Deserialize,
@@ -27,6 +30,7 @@ use serde::{
 use std::{
     default::Default,
     fmt::Debug,
+    sync::Arc,
 };
 
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -46,8 +50,9 @@ where
     kept_handles: Vec<u64>,
 
     // The subquadrants under this cell. [ne, nw, se, sw]. If there are no subquadrants, this
-    // entire list could be None.
-    subquadrants: Option<Box<[Self; 4]>>,
+    // entire list could be None. Shared between clones of this cell, and copied on write; see
+    // subquadrants_mut().
+    subquadrants: Option<Arc<[Self; 4]>>,
 
     // The last-inserted handle. This is a monotonically increasing counter.
     handle_counter: u64,
@@ -58,14 +63,8 @@ where
     U: PrimInt + Default + Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if self.subquadrants.is_some() {
-            write!(
-                f,
-                "{:?} :: {:?} {:#?}",
-                self.region,
-                self.kept_handles,
-                self.subquadrants.as_ref().unwrap()
-            )
+        if let Some(sqs) = self.subquadrants.as_ref() {
+            write!(f, "{:?} :: {:?} {:#?}", self.region, self.kept_handles, sqs)
         } else {
             write!(f, "{:?} :: {:?}", self.region, self.kept_handles,)
         }
@@ -78,11 +77,14 @@ where
 {
     // pub
 
-    pub fn new(anchor: Point<U>, depth: usize) -> Self {
-        #[allow(clippy::cast_possible_truncation)]
-        let width: U = Self::two().pow(depth as u32);
+    // Fails if the width (2^depth) or the far edges of the region can't be represented by U.
+    pub fn try_new(anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        let width: U = num::checked_pow(Self::two(), depth).ok_or(Error::Overflow)?;
         let height: U = width;
-        Self::new_with_area((anchor, (width, height)).into(), depth)
+        Ok(Self::new_with_area(
+            Area::new(anchor, (width, height))?,
+            depth,
+        ))
     }
 
     pub fn depth(&self) -> usize {
@@ -116,18 +118,99 @@ where
         store: &mut StoreType<U, V>,
     ) -> u64 {
         let req = req.into();
-        let handle = self.handle_counter;
-        self.handle_counter += 1;
+        let handle = self.next_handle();
         store.insert(handle, Entry::new((req, val), handle));
-        self.insert_handle_at_region(req, handle, store);
+        self.insert_handle_at_region(req, handle);
         handle
     }
 
+    // Files an existing entry under its own handle, rather than minting a new one. Keeps the
+    // handle counter ahead of every handle in the tree, so future handles stay unique.
+    // Expects the entry's region to fit within this node.
+    pub fn insert_entry<V>(&mut self, entry: Entry<U, V>, store: &mut StoreType<U, V>) {
+        let handle = entry.handle();
+        let region = entry.area();
+        self.handle_counter = self.handle_counter.max(handle + 1);
+        store.insert(handle, entry);
+        self.insert_handle_at_region(region, handle);
+    }
+
+    // An empty tree with the same region, depth, and handle counter as this one.
+    pub fn empty_like(&self) -> Self {
+        Self {
+            handle_counter: self.handle_counter,
+            ..Self::new_with_area(self.region, self.depth)
+        }
+    }
+
+    // An empty tree large enough to hold this tree's region and every one of @regions, keeping
+    // this tree's handle counter. The anchor moves up and left only as far as it needs to, and
+    // the depth only grows. Returns None if this tree already covers @regions.
+    pub fn grown_to_cover(
+        &self,
+        regions: impl IntoIterator<Item = Area<U>>,
+    ) -> Result<Option<Self>, Error> {
+        let root = self.region;
+        let (mut left, mut top) = (root.left_edge(), root.top_edge());
+        let (mut right, mut bottom) = (root.right_edge(), root.bottom_edge());
+        for r in regions {
+            left = left.min(r.left_edge());
+            top = top.min(r.top_edge());
+            right = right.max(r.right_edge());
+            bottom = bottom.max(r.bottom_edge());
+        }
+        if (left, top, right, bottom)
+            == (
+                root.left_edge(),
+                root.top_edge(),
+                root.right_edge(),
+                root.bottom_edge(),
+            )
+        {
+            return Ok(None);
+        }
+
+        let width = right.checked_sub(&left).ok_or(Error::Overflow)?;
+        let height = bottom.checked_sub(&top).ok_or(Error::Overflow)?;
+        let bounds = Area::new((left, top), (width, height))?;
+        self.try_reshaped_to_cover(bounds).map(Some)
+    }
+
+    // An empty tree anchored at @region's anchor, with the smallest depth no less than this
+    // tree's which covers @region. Keeps this tree's handle counter.
+    pub fn try_reshaped_to_cover(&self, region: Area<U>) -> Result<Self, Error> {
+        let extent = region.width().max(region.height());
+        let mut depth = self.depth;
+        while num::checked_pow(Self::two(), depth).ok_or(Error::Overflow)? < extent {
+            depth += 1;
+        }
+        self.try_reshaped(region.anchor(), depth)
+    }
+
+    // An empty tree at @anchor and @depth which keeps this tree's handle counter.
+    pub fn try_reshaped(&self, anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        Ok(Self {
+            handle_counter: self.handle_counter,
+            ..Self::try_new(anchor, depth)?
+        })
+    }
+
+    // Shifts the region of this node and every node below it by @offset. Callers must ensure
+    // that the shifted root still fits in U.
+    pub fn translate(&mut self, offset: Point<U>) {
+        self.region = Area::new_unchecked(self.region.anchor() + offset, self.region.dimensions());
+        if let Some(sqs) = self.subquadrants_mut() {
+            for sq in sqs.iter_mut() {
+                sq.translate(offset);
+            }
+        }
+    }
+
     // Delete all instances of @handle from this level's @kept_handles.
     pub fn delete_by_handle(&mut self, handle: u64, req: Area<U>) {
         self.kept_handles.retain(|&x| x != handle);
         // And potentially recurse into the subquadrants...
-        if let Some(sqs) = self.subquadrants.as_mut() {
+        if let Some(sqs) = self.subquadrants_mut() {
             for sq in sqs.iter_mut() {
                 // ...but not all of them.
                 if sq.region.intersects(req) {
@@ -137,8 +220,115 @@ where
         }
     }
 
+    // Re-files @handle from the nodes covering @old to the nodes covering @new. Only descends
+    // where the two placements differ, so small moves within the same nodes touch very little.
+    // Expects both regions to fit within this node.
+    pub fn move_handle(&mut self, handle: u64, old: Area<U>, new: Area<U>) {
+        match (self.keeps(old), self.keeps(new)) {
+            // Both are held here, and no lower.
+            (true, true) => {}
+            (true, false) => {
+                self.kept_handles.retain(|&x| x != handle);
+                self.insert_handle_at_region(new, handle);
+            }
+            (false, true) => {
+                self.delete_by_handle(handle, old);
+                self.kept_handles.push(handle);
+            }
+            (false, false) => {
+                if self.subquadrants.is_none() {
+                    self.expand_subquadrants_by_pt(self.region.center_pt());
+                }
+                if let Some(sqs) = self.subquadrants_mut() {
+                    for sq in sqs.iter_mut() {
+                        match (sq.region.intersects(old), sq.region.intersects(new)) {
+                            (true, true) => sq.move_handle(handle, old, new),
+                            (true, false) => sq.delete_by_handle(handle, old),
+                            (false, true) => sq.insert_handle_at_region(new, handle),
+                            (false, false) => {}
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Files each handle in @reqs at every node which should hold its region, as
+    // insert_handle_at_region() would if called on each in order, but with the subquadrants
+    // filled in parallel. Expects every region to fit within this node.
+    #[cfg(feature = "rayon")]
+    pub fn par_insert_handles_at_regions(&mut self, reqs: &[(Area<U>, u64)])
+    where
+        U: Send + Sync,
+    {
+        // Below this many handles, splitting the work costs more than it saves.
+        const SEQUENTIAL: usize = 64;
+        if reqs.len() < SEQUENTIAL {
+            for (req, handle) in reqs {
+                self.insert_handle_at_region(*req, *handle);
+            }
+            return;
+        }
+
+        let mut below = Vec::with_capacity(reqs.len());
+        for (req, handle) in reqs {
+            if self.keeps(*req) {
+                self.kept_handles.push(*handle);
+            } else {
+                below.push((*req, *handle));
+            }
+        }
+        if below.is_empty() {
+            return;
+        }
+
+        if self.subquadrants.is_none() {
+            self.expand_subquadrants_by_pt(self.region.center_pt());
+        }
+        if let Some(sqs) = self.subquadrants_mut() {
+            sqs.par_iter_mut().for_each(|sq| {
+                let reqs: Vec<(Area<U>, u64)> = below
+                    .iter()
+                    .filter(|(req, _)| sq.region.intersects(*req))
+                    .copied()
+                    .collect();
+                if !reqs.is_empty() {
+                    sq.par_insert_handles_at_regions(&reqs);
+                }
+            });
+        }
+    }
+
+    // Mints a new handle.
+    pub fn next_handle(&mut self) -> u64 {
+        let handle = self.handle_counter;
+        self.handle_counter += 1;
+        handle
+    }
+
+    // The next handle to be minted.
+    pub fn handle_counter(&self) -> u64 {
+        self.handle_counter
+    }
+
+    pub fn set_handle_counter(&mut self, handle_counter: u64) {
+        self.handle_counter = handle_counter;
+    }
+
     // fn
 
+    // Mutable access to the subquadrants. If they're shared with a clone of this cell, they're
+    // copied first, so only the nodes along the mutated path are ever duplicated.
+    fn subquadrants_mut(&mut self) -> Option<&mut [Self; 4]> {
+        self.subquadrants.as_mut().map(Arc::make_mut)
+    }
+
+    // Whether a handle for @req, which intersects this node, is held at this node rather than
+    // being passed down to the subquadrants. Mirrors the logic in insert_handle_at_region().
+    fn keeps(&self, req: Area<U>) -> bool {
+        self.depth == 0 || req.contains(self.region)
+    }
+
     fn new_with_area(region: Area<U>, depth: usize) -> Self {
         Self {
             depth,
@@ -149,14 +339,8 @@ where
         }
     }
 
-    // Attempts to insert the value at the requested region. Returns false if the region was too
-    // large.
-    fn insert_handle_at_region<V>(
-        &mut self,
-        req: Area<U>,
-        handle: u64,
-        _store: &mut StoreType<U, V>,
-    ) {
+    // Files @handle at every node which should hold @req. Expects @req to fit within this node.
+    pub fn insert_handle_at_region(&mut self, req: Area<U>, handle: u64) {
         // If we're at the bottom depth, it had better fit.
         if self.depth == 0 {
             self.kept_handles.push(handle);
@@ -179,10 +363,10 @@ where
 
         assert!(self.subquadrants.is_some()); // We should have Someified this in .split().
 
-        if let Some(sqs) = self.subquadrants.as_mut() {
+        if let Some(sqs) = self.subquadrants_mut() {
             for sq in sqs.iter_mut() {
                 if sq.region.intersects(req) {
-                    sq.insert_handle_at_region(req, handle, _store);
+                    sq.insert_handle_at_region(req, handle);
                 }
             }
         }
@@ -196,27 +380,30 @@ where
     fn expand_subquadrants_by_pt(&mut self, p: Point<U>) {
         assert!(self.region.contains_pt(p));
 
-        self.subquadrants = Some(Box::new([
+        // Each subquadrant is a quarter of this (already valid) region, so needn't be checked.
+        let dimensions = (
+            self.region.width() / Self::two(),
+            self.region.height() / Self::two(),
+        );
+        let child = |anchor: Point<U>| {
+            Self::new_with_area(Area::new_unchecked(anchor, dimensions), self.depth - 1)
+        };
+
+        self.subquadrants = Some(Arc::new([
             // Northeast
-            Self::new(
-                Point {
-                    x: p.x(),
-                    y: self.region.anchor().y(),
-                },
-                self.depth - 1,
-            ),
+            child(Point {
+                x: p.x(),
+                y: self.region.anchor().y(),
+            }),
             // Northwest
-            Self::new(self.region.anchor(), self.depth - 1),
+            child(self.region.anchor()),
             // Southeast
-            Self::new(p, self.depth - 1),
+            child(p),
             // Southwest
-            Self::new(
-                Point {
-                    x: self.region.anchor().x(),
-                    y: p.y(),
-                },
-                self.depth - 1,
-            ),
+            child(Point {
+                x: self.region.anchor().x(),
+                y: p.y(),
+            }),
         ]));
     }
 
@@ -13,18 +13,24 @@
 // limitations under the License.
 
 use crate::{
-    area::{
-        Area,
-        AreaBuilder,
+    area::Area,
+    entry::{
+        Entry,
+        RegionEntry,
     },
-    entry::Entry,
+    error::Error,
     handle_iter::HandleIter,
     iter::{
+        Drain,
+        IntersectingPairs,
         IntoIter,
         Iter,
+        IterMut,
         Query,
+        QueryMut,
         Regions,
         Values,
+        ValuesMut,
     },
     point::Point,
     qtinner::QTInner,
@@ -42,8 +48,12 @@ use std::{
         HashMap,
         HashSet,
     },
+    convert::TryInto,
     default::Default,
-    hash::Hash,
+    hash::{
+        Hash,
+        Hasher,
+    },
 };
 
 /// A data structure for storing and accessing data in 2d space.
@@ -73,11 +83,10 @@ use std::{
 /// [`.query()`]: #method.query
 /// [`.modify()`]: #method.modify
 /// [`.delete()`]: #method.delete
-// TODO(ambuc): Implement `.delete_by(anchor, dimensions, fn)`: `.retain()` is the inverse.
 // TODO(ambuc): Implement `FromIterator<(K, V)>` for `Quadtree`.
 #[cfg_attr(feature = "bevy", derive(bevy::prelude::Resource))]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Quadtree<U, V>
 where
     U: PrimInt + Default,
@@ -94,6 +103,12 @@ where
 
     /// Creates a new, empty quadtree with some depth.
     /// A quadtree with depth `n` will accept coordinates in the range `[0, 2^n]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `2^n` can't be represented by `U`. See [`.try_new()`] for a fallible variant.
+    ///
+    /// [`.try_new()`]: #method.try_new
     /// ```
     /// use quadtree_rs::{point::Point, Quadtree};
     ///
@@ -122,7 +137,13 @@ where
     /// anchor argument is of type [`point::Point`], and can either be
     /// explicit (`Point {x: 2, y: 4}`) or implicit (`(2, 4).into()`).
     ///
+    /// # Panics
+    ///
+    /// Panics if the region can't be represented by `U`. See [`.try_new_with_anchor()`] for a
+    /// fallible variant.
+    ///
     /// [`point::Point`]: point/struct.Point.html
+    /// [`.try_new_with_anchor()`]: #method.try_new_with_anchor
     /// ```
     /// use quadtree_rs::{point::Point, Quadtree};
     ///
@@ -136,10 +157,44 @@ where
     /// assert_eq!(qt.height(), 8);
     /// ```
     pub fn new_with_anchor(anchor: Point<U>, depth: usize) -> Self {
-        Self {
-            inner: QTInner::new(anchor, depth),
+        Self::try_new_with_anchor(anchor, depth)
+            .expect("The quadtree's region can't be represented by its coordinate type.")
+    }
+
+    /// A fallible variant of [`.new()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth` can't be represented by `U`.
+    /// ```
+    /// use quadtree_rs::{Error, Quadtree};
+    ///
+    /// assert!(Quadtree::<u8, ()>::try_new(7).is_ok());
+    /// assert_eq!(Quadtree::<u8, ()>::try_new(8), Err(Error::Overflow));
+    /// ```
+    ///
+    /// [`.new()`]: #method.new
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn try_new(depth: usize) -> Result<Self, Error> {
+        Self::try_new_with_anchor(
+            Point {
+                x: U::zero(),
+                y: U::zero(),
+            },
+            depth,
+        )
+    }
+
+    /// A fallible variant of [`.new_with_anchor()`].
+    ///
+    /// Returns [`Error::Overflow`] if `2^depth`, or the bottom or right edge of the region, can't
+    /// be represented by `U`.
+    ///
+    /// [`.new_with_anchor()`]: #method.new_with_anchor
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn try_new_with_anchor(anchor: Point<U>, depth: usize) -> Result<Self, Error> {
+        Ok(Self {
+            inner: QTInner::try_new(anchor, depth)?,
             store: HashMap::new(),
-        }
+        })
     }
 
     /// The top-left corner (anchor) of the region which this quadtree represents.
@@ -148,13 +203,21 @@ where
     }
 
     /// The width of the region which this quadtree represents.
+    ///
+    /// Saturates at `usize::MAX` if the width doesn't fit in a `usize`.
     pub fn width(&self) -> usize {
-        self.inner.region().width().to_usize().unwrap()
+        self.inner.region().width().to_usize().unwrap_or(usize::MAX)
     }
 
     /// The height of the region which this quadtree represents.
+    ///
+    /// Saturates at `usize::MAX` if the height doesn't fit in a `usize`.
     pub fn height(&self) -> usize {
-        self.inner.region().height().to_usize().unwrap()
+        self.inner
+            .region()
+            .height()
+            .to_usize()
+            .unwrap_or(usize::MAX)
     }
 
     /// The depth of the quadtree.
@@ -173,22 +236,24 @@ where
     }
 
     /// Whether or not some trial region could fit in the region which this quadtree represents.
-    pub fn contains(&self, area: impl Into<Area<U>>) -> bool {
-        self.inner.region().contains(area.into())
+    pub fn contains(&self, area: impl TryInto<Area<U>>) -> bool {
+        self.inner.region().contains(area)
     }
 
     /// Associate some value with a region in the quadtree.
     ///
     /// If insertion is successful, returns a unique handle to the value.
     ///
-    /// If the region is too large for, or doesn't overlap with, the region which this quadtree
-    /// represents, returns `None`.
+    /// If the region is invalid, or is too large for, or doesn't overlap with, the region which
+    /// this quadtree represents, returns `None`. See [`.try_insert()`] to find out why.
+    ///
+    /// [`.try_insert()`]: #method.try_insert
     /// ```
     /// use quadtree_rs::{area::Area, Quadtree};
     ///
     /// let mut qt = Quadtree::<u32, i8>::new(8);
     ///
-    /// let region: Area<u32> = ((4,5),(2,3)).into();
+    /// let region: Area<u32> = Area::new((4,5), (2,3)).unwrap();
     ///
     /// let handle_a_1 = qt.insert(region, 5).unwrap();
     /// let handle_a_2 = qt.insert(region, 5).unwrap();
@@ -197,15 +262,40 @@ where
     /// // two handles returned were not the same.
     /// assert_ne!(handle_a_1, handle_a_2);
     /// ```
-    pub fn insert(&mut self, region: impl Into<Area<U>>, val: V) -> Option<u64> {
-        let region = region.into();
-        if self.contains(region) {
-            return Some(
-                self.inner
-                    .insert_val_at_region(region, val, &mut self.store),
-            );
+    pub fn insert(&mut self, region: impl TryInto<Area<U>>, val: V) -> Option<u64> {
+        self.try_insert(region.try_into().ok()?, val).ok()
+    }
+
+    /// A fallible variant of [`.insert()`].
+    ///
+    /// Returns [`Error::InvalidDimensions`] or [`Error::Overflow`] if the region can't be built,
+    /// and [`Error::OutOfBounds`] if it doesn't fit in the region which this quadtree represents.
+    /// ```
+    /// use quadtree_rs::{Error, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, i8>::new(2);
+    ///
+    /// assert!(qt.try_insert(((0,0),(2,3)), 5).is_ok());
+    /// assert_eq!(qt.try_insert(((0,0),(2,0)), 5), Err(Error::InvalidDimensions));
+    /// assert_eq!(qt.try_insert(((3,3),(2,2)), 5), Err(Error::OutOfBounds));
+    /// ```
+    ///
+    /// [`.insert()`]: #method.insert
+    /// [`Error::InvalidDimensions`]: error/enum.Error.html#variant.InvalidDimensions
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    /// [`Error::OutOfBounds`]: error/enum.Error.html#variant.OutOfBounds
+    pub fn try_insert<A>(&mut self, region: A, val: V) -> Result<u64, Error>
+    where
+        A: TryInto<Area<U>>,
+        Error: From<A::Error>,
+    {
+        let region = region.try_into()?;
+        if !self.contains(region) {
+            return Err(Error::OutOfBounds);
         }
-        None
+        Ok(self
+            .inner
+            .insert_val_at_region(region, val, &mut self.store))
     }
 
     /// Alias for [`.insert()`] which expects a [`Point`] instead of an [`Area`].
@@ -225,10 +315,44 @@ where
     /// [`Area`]: area/struct.Area.html
     /// [`Point`]: point/struct.Point.html
     pub fn insert_pt(&mut self, point: Point<U>, val: V) -> Option<u64> {
-        if let Ok(area) = AreaBuilder::default().anchor(point).build() {
-            return self.insert(area, val);
+        self.insert(point, val)
+    }
+
+    /// Gets the [`RegionEntry`] for exactly some region, for in-place manipulation.
+    ///
+    /// Modelled on [`HashMap::entry()`], where the region plays the part of the key. Values
+    /// associated with regions which merely overlap this one are not considered.
+    ///
+    /// Returns an error if the region is invalid, or doesn't fit in the region which this
+    /// quadtree represents. See [`.try_insert()`].
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u32>::new(4);
+    ///
+    /// let region = Area::new((1,1), (2,2)).unwrap();
+    /// for _ in 0..3 {
+    ///     qt.entry(region).unwrap().and_modify(|v| *v += 1).or_insert(0);
+    /// }
+    ///
+    /// // Only one value was ever associated with the region.
+    /// assert_eq!(qt.len(), 1);
+    /// assert_eq!(qt.query(region).next().unwrap().value_ref(), &2);
+    /// ```
+    ///
+    /// [`RegionEntry`]: entry/enum.RegionEntry.html
+    /// [`HashMap::entry()`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.entry
+    /// [`.try_insert()`]: #method.try_insert
+    pub fn entry<A>(&mut self, area: A) -> Result<RegionEntry<'_, U, V>, Error>
+    where
+        A: TryInto<Area<U>>,
+        Error: From<A::Error>,
+    {
+        let area = area.try_into()?;
+        if !self.contains(area) {
+            return Err(Error::OutOfBounds);
         }
-        None
+        Ok(RegionEntry::new(self, area))
     }
 
     /// Given the handle from an [`.insert()`] operation, provides read-only
@@ -242,7 +366,7 @@ where
     ///
     /// let mut qt = Quadtree::<u32, f32>::new(4);
     ///
-    /// let region: Area<u32> = ((0,1),(2,3)).into();
+    /// let region: Area<u32> = Area::new((0,1), (2,3)).unwrap();
     /// let handle = qt.insert(region, 9.87).unwrap();
     ///
     /// let entry = qt.get(handle).unwrap();
@@ -263,7 +387,7 @@ where
     ///
     /// let mut qt = Quadtree::<u32, f32>::new(4);
     ///
-    /// let region: Area<u32> = ((0,1),(2,3)).into();
+    /// let region: Area<u32> = Area::new((0,1), (2,3)).unwrap();
     /// let handle: u64 = qt.insert(region, 9.87).unwrap();
     ///
     /// if let Some(entry) = qt.get_mut(handle) {
@@ -294,10 +418,10 @@ where
     /// // 5 ░░░░░░░
     /// let mut qt = Quadtree::<u32, char>::new(4);
     ///
-    /// let region_a: Area<u32> = ((2,1),(3,2)).into();
+    /// let region_a: Area<u32> = Area::new((2,1), (3,2)).unwrap();
     /// qt.insert(region_a, 'a');
     ///
-    /// let region_b: Area<u32> = ((1,4),(3,1)).into();
+    /// let region_b: Area<u32> = Area::new((1,4), (3,1)).unwrap();
     /// qt.insert(region_b, 'b');
     ///
     /// //   0123456
@@ -325,7 +449,7 @@ where
     /// // 3 ░▒▒▒▒▒░
     /// // 4 ░▓▓▓▒▒░
     /// // 5 ░░░░░░░
-    /// let region_d: Area<u32> = ((1,1),(4,4)).into();
+    /// let region_d: Area<u32> = Area::new((1,1), (4,4)).unwrap();
     /// let query_b = qt.query(region_d);
     ///
     /// // It's unspecified what order the regions should
@@ -333,23 +457,91 @@ where
     /// assert_eq!(query_b.count(), 2);
     /// ```
     ///
+    /// An invalid query region (i.e. one with a nonpositive width or height) contains nothing, so
+    /// querying over it returns nothing.
+    ///
     /// [`&Entry<U, V>`]: entry/struct.Entry.html
     /// [`.query()`]: #method.query
     // TODO(ambuc): Settle on a stable return order to avoid breaking callers.
-    pub fn query(&self, area: impl Into<Area<U>>) -> Query<U, V> {
-        Query::new(area, &self.inner, &self.store, Traversal::Overlapping)
+    pub fn query(&self, area: impl TryInto<Area<U>>) -> Query<'_, U, V> {
+        Query::new(
+            area.try_into().ok(),
+            &self.inner,
+            &self.store,
+            Traversal::Overlapping,
+        )
     }
 
     /// A strict variant of [`.query()`].
     ///
     /// [`.query()`]: #method.query
-    pub fn query_strict(&self, area: impl Into<Area<U>>) -> Query<U, V> {
-        Query::new(area, &self.inner, &self.store, Traversal::Strict)
+    pub fn query_strict(&self, area: impl TryInto<Area<U>>) -> Query<'_, U, V> {
+        Query::new(
+            area.try_into().ok(),
+            &self.inner,
+            &self.store,
+            Traversal::Strict,
+        )
+    }
+
+    /// A mutable variant of [`.query()`], returning an iterator over [`&mut Entry<U, V>`]
+    /// structs.
+    ///
+    /// The matching entries are found by descending the tree, as for [`.query()`], but are then
+    /// picked out of a scan of the quadtree's entries. This takes time proportional to the
+    /// number of entries in the quadtree, not just the number which match.
+    ///
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    ///
+    /// let region_a: Area<u32> = Area::new((0,0), (2,2)).unwrap();
+    /// let handle_a = qt.insert(region_a, 1).unwrap();
+    ///
+    /// let region_b: Area<u32> = Area::new((8,8), (2,2)).unwrap();
+    /// let handle_b = qt.insert(region_b, 1).unwrap();
+    ///
+    /// let mut seen = vec![];
+    /// for entry in qt.query_mut(((1,1),(2,2))) {
+    ///     *entry.value_mut() += 1;
+    ///     seen.push(entry.handle());
+    /// }
+    ///
+    /// assert_eq!(seen, vec![handle_a]);
+    /// assert_eq!(qt.get(handle_a).unwrap().value_ref(), &2);
+    /// assert_eq!(qt.get(handle_b).unwrap().value_ref(), &1);
+    /// ```
+    ///
+    /// [`.query()`]: #method.query
+    /// [`&mut Entry<U, V>`]: entry/struct.Entry.html
+    pub fn query_mut(&mut self, area: impl TryInto<Area<U>>) -> QueryMut<'_, U, V> {
+        QueryMut::new(
+            area.try_into().ok(),
+            &self.inner,
+            &mut self.store,
+            Traversal::Overlapping,
+        )
+    }
+
+    /// A strict variant of [`.query_mut()`].
+    ///
+    /// [`.query_mut()`]: #method.query_mut
+    pub fn query_strict_mut(&mut self, area: impl TryInto<Area<U>>) -> QueryMut<'_, U, V> {
+        QueryMut::new(
+            area.try_into().ok(),
+            &self.inner,
+            &mut self.store,
+            Traversal::Strict,
+        )
     }
 
     /// Accepts a modification lambda and applies it to all elements in the
     /// quadtree which intersecting the described region.
     ///
+    /// Like [`.query_mut()`], this scans the quadtree's entries, and so takes time proportional
+    /// to the number of entries in the quadtree.
+    ///
     /// ```
     /// use quadtree_rs::{area::Area, Quadtree};
     ///
@@ -363,23 +555,33 @@ where
     ///
     /// // ...and verify that the value was applied.
     /// assert_eq!(qt.get(handle).unwrap().value_ref(), &false);
+    ///
+    /// // The lambda may capture mutable state, too.
+    /// let mut count = 0;
+    /// qt.modify(region_a, |_| count += 1);
+    /// assert_eq!(count, 1);
     /// ```
-    pub fn modify<F>(&mut self, area: impl Into<Area<U>>, f: F)
+    ///
+    /// [`.query_mut()`]: #method.query_mut
+    pub fn modify<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F)
     where
-        F: Fn(&mut V) + Copy,
+        F: FnMut(&mut V),
     {
-        let area = area.into();
-        self.modify_region(|a| a.intersects(area), f);
+        for entry in self.query_mut(area) {
+            f(entry.value_mut());
+        }
     }
 
-    /// A strict variant of [`.modify()`].
+    /// A strict variant of [`.modify()`]. Like it, this scans the quadtree's entries.
     ///
     /// [`.modify()`]: #method.modify
-    pub fn modify_strict<F>(&mut self, area: Area<U>, f: F)
+    pub fn modify_strict<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F)
     where
-        F: Fn(&mut V) + Copy,
+        F: FnMut(&mut V),
     {
-        self.modify_region(|a| area.contains(a), f);
+        for entry in self.query_strict_mut(area) {
+            f(entry.value_mut());
+        }
     }
 
     /// Alias for [`.modify()`] which runs over the entire
@@ -388,11 +590,9 @@ where
     /// [`.modify()`]: #method.modify
     pub fn modify_all<F>(&mut self, f: F)
     where
-        F: Fn(&mut V) + Copy,
+        F: FnMut(&mut V),
     {
-        for entry in self.store.values_mut() {
-            f(entry.value_mut());
-        }
+        self.values_mut().for_each(f);
     }
 
     /// Resets the quadtree to a totally empty state.
@@ -410,10 +610,10 @@ where
     ///
     /// let mut qt = Quadtree::<u32, f64>::new(4);
     ///
-    /// let region_a: Area<u32> = ((0,0),(2,2)).into();
+    /// let region_a: Area<u32> = Area::new((0,0), (2,2)).unwrap();
     /// qt.insert(region_a, 1.23);
     ///
-    /// let region_b: Area<u32> = ((1,1),(3,2)).into();
+    /// let region_b: Area<u32> = Area::new((1,1), (3,2)).unwrap();
     /// qt.insert(region_b, 4.56);
     ///
     /// //   0123
@@ -435,32 +635,278 @@ where
     /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
     /// [`Entry<U, V>`]: entry/struct.Entry.html
     /// [`.delete()`]: #method.delete
-    pub fn delete(&mut self, area: impl Into<Area<U>>) -> IntoIter<U, V> {
+    pub fn delete(&mut self, area: impl TryInto<Area<U>>) -> IntoIter<U, V> {
         self.delete_handles_and_return(self.query(area).map(|e| e.handle()).collect())
     }
 
     /// A strict variant of [`.delete()`].
     ///
     /// [`.delete()`]: #method.delete
-    pub fn delete_strict(&mut self, area: Area<U>) -> IntoIter<U, V> {
+    pub fn delete_strict(&mut self, area: impl TryInto<Area<U>>) -> IntoIter<U, V> {
         self.delete_handles_and_return(self.query_strict(area).map(|e| e.handle()).collect())
     }
 
+    // TODO(ambuc): There is an optimization here to do one traversal with many matches, over
+    // many traversals i.e. one per match.
     #[allow(clippy::needless_pass_by_value)]
     fn delete_handles_and_return(&mut self, handles: HashSet<u64>) -> IntoIter<U, V> {
-        let error: &'static str = "I tried to look up an handle in the store which I found in the tree, but it wasn't there!";
-
         let mut entries: Vec<Entry<U, V>> = vec![];
 
-        handles.iter().for_each(|u| {
-            // We were just passed a hashset of handles taken from this quadtree, so it is safe to
-            // assume they all still exist.
-            entries.push(self.store.remove(u).expect(error));
-        });
+        for handle in handles {
+            // We were just passed a hashset of handles taken from this quadtree, so they should
+            // all still exist in the store.
+            if let Some(entry) = self.store.remove(&handle) {
+                self.inner.delete_by_handle(handle, entry.area());
+                entries.push(entry);
+            }
+        }
 
         IntoIter { entries }
     }
 
+    // Swaps in @inner, which must be empty, and files every entry in the store into it under
+    // its existing handle. Entries which don't fit in @inner are removed and returned.
+    fn reindex(&mut self, inner: QTInner<U>) -> Vec<Entry<U, V>> {
+        self.inner = inner;
+        let region = self.inner.region();
+        let entries: Vec<Entry<U, V>> = self.store.drain().map(|(_, e)| e).collect();
+        let mut evicted = vec![];
+        for entry in entries {
+            if region.contains(entry.area()) {
+                self.inner.insert_entry(entry, &mut self.store);
+            } else {
+                evicted.push(entry);
+            }
+        }
+        evicted
+    }
+
+    /// Deletes all value associations in a region of the tree for which the predicate returns
+    /// `true`, leaving the rest in place.
+    ///
+    /// Whether a value is in the region is decided by `traversal`, as for [`.delete()`] and
+    /// [`.delete_strict()`]. Consumed [`Entry<U, V>`] entries are returned in an iterator
+    /// [`IntoIter<U, V>`].
+    ///
+    /// Like [`.query_mut()`], this scans the quadtree's entries, and so takes time proportional
+    /// to the number of entries in the quadtree.
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree, Traversal};
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((1, 1), 2), ((2, 2), 3), ((9, 9), 4)]);
+    ///
+    /// let region = Area::new((0, 0), (4, 4)).unwrap();
+    /// let odds: Vec<u8> = qt
+    ///     .drain_filter(region, Traversal::Strict, |v| *v % 2 == 1)
+    ///     .map(|e| *e.value_ref())
+    ///     .collect();
+    ///
+    /// assert_eq!(odds.len(), 2);
+    /// assert!(odds.contains(&1) && odds.contains(&3));
+    ///
+    /// // The even value in the region, and the odd value outside it, were left behind.
+    /// assert_eq!(qt.len(), 2);
+    /// ```
+    ///
+    /// [`.delete()`]: #method.delete
+    /// [`.delete_strict()`]: #method.delete_strict
+    /// [`.query_mut()`]: #method.query_mut
+    /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
+    /// [`Entry<U, V>`]: entry/struct.Entry.html
+    pub fn drain_filter<F>(
+        &mut self,
+        area: impl TryInto<Area<U>>,
+        traversal: Traversal,
+        mut pred: F,
+    ) -> IntoIter<U, V>
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        let handles = QueryMut::new(
+            area.try_into().ok(),
+            &self.inner,
+            &mut self.store,
+            traversal,
+        )
+        .filter_map(|e| pred(e.value_mut()).then_some(e.handle()))
+        .collect();
+        self.delete_handles_and_return(handles)
+    }
+
+    /// Removes every value association from the quadtree, returning them lazily in an iterator
+    /// ([`Drain<U, V>`]) over owned [`Entry<U, V>`] entries.
+    ///
+    /// The quadtree is emptied even if the iterator is dropped before it is exhausted.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((1, 1), 2)]);
+    ///
+    /// let mut total = 0;
+    /// for entry in qt.drain() {
+    ///     total += entry.value_ref();
+    /// }
+    ///
+    /// assert_eq!(total, 3);
+    /// assert!(qt.is_empty());
+    /// ```
+    ///
+    /// [`Drain<U, V>`]: iter/struct.Drain.html
+    /// [`Entry<U, V>`]: entry/struct.Entry.html
+    pub fn drain(&mut self) -> Drain<'_, U, V> {
+        self.inner.reset();
+        Drain {
+            inner: self.store.drain(),
+        }
+    }
+
+    /// Moves every value association in a region of the tree into a new quadtree, and returns it.
+    ///
+    /// Whether a value is in the region is decided by `traversal`, as for [`.delete()`] and
+    /// [`.delete_strict()`]. The returned quadtree has the same anchor and depth as this one, and
+    /// each moved value keeps its handle. Those handles are no longer valid in `self`.
+    /// ```
+    /// use quadtree_rs::{Quadtree, Traversal};
+    ///
+    /// let mut west = Quadtree::<u32, char>::new(4);
+    /// let handle_a = west.insert(((1, 1), (2, 2)), 'a').unwrap();
+    /// let handle_b = west.insert(((12, 1), (2, 2)), 'b').unwrap();
+    ///
+    /// let east = west.split_off(((8, 0), (8, 16)), Traversal::Strict);
+    ///
+    /// assert_eq!(west.get(handle_a).unwrap().value_ref(), &'a');
+    /// assert!(west.get(handle_b).is_none());
+    /// assert_eq!(east.get(handle_b).unwrap().value_ref(), &'b');
+    /// assert_eq!(east.anchor(), west.anchor());
+    /// ```
+    ///
+    /// [`.delete()`]: #method.delete
+    /// [`.delete_strict()`]: #method.delete_strict
+    pub fn split_off(&mut self, area: impl TryInto<Area<U>>, traversal: Traversal) -> Self {
+        let handles: HashSet<u64> =
+            Query::new(area.try_into().ok(), &self.inner, &self.store, traversal)
+                .map(|e| e.handle())
+                .collect();
+
+        let mut other = Self {
+            inner: self.inner.empty_like(),
+            store: HashMap::new(),
+        };
+        for entry in self.delete_handles_and_return(handles) {
+            other.inner.insert_entry(entry, &mut other.store);
+        }
+        other
+    }
+
+    /// Moves every value association out of `other` and into this quadtree, leaving `other` empty.
+    ///
+    /// If `other` holds regions which don't fit in this quadtree, the root is grown to cover them
+    /// first: its anchor moves up and to the left as needed, and its depth increases. Values
+    /// already in this quadtree keep their handles.
+    ///
+    /// Both quadtrees hand out handles from their own counters, so the moved values are given new
+    /// handles. Returns a map from each value's handle in `other` to its new handle in `self`.
+    ///
+    /// Fails with [`Error::Overflow`] if the grown root can't be represented by `U`, in which case
+    /// neither quadtree is changed.
+    /// ```
+    /// use quadtree_rs::{area::Area, point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(2);
+    /// let handle_a = qt.insert((1, 1), 'a').unwrap();
+    ///
+    /// let mut other = Quadtree::<u32, char>::new_with_anchor((8, 8).into(), 2);
+    /// let handle_b = other.insert((9, 9), 'b').unwrap();
+    ///
+    /// let remapping = qt.append(&mut other).unwrap();
+    /// assert!(other.is_empty());
+    ///
+    /// // The root has grown to cover (9, 9).
+    /// assert_eq!(qt.anchor(), Point::from((0, 0)));
+    /// assert_eq!(qt.depth(), 4);
+    ///
+    /// assert_eq!(qt.get(handle_a).unwrap().value_ref(), &'a');
+    /// let entry_b = qt.get(remapping[&handle_b]).unwrap();
+    /// assert_eq!(entry_b.value_ref(), &'b');
+    /// assert_eq!(entry_b.area(), Area::from((9, 9)));
+    /// ```
+    ///
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn append(&mut self, other: &mut Self) -> Result<HashMap<u64, u64>, Error> {
+        if let Some(inner) = self.inner.grown_to_cover(other.regions())? {
+            // The grown root covers every region, so nothing is evicted.
+            self.reindex(inner);
+        }
+
+        let mut remapping = HashMap::with_capacity(other.len());
+        for entry in other.drain() {
+            let (old_handle, region) = (entry.handle(), entry.area());
+            let new_handle =
+                self.inner
+                    .insert_val_at_region(region, entry.into_value(), &mut self.store);
+            remapping.insert(old_handle, new_handle);
+        }
+        Ok(remapping)
+    }
+
+    /// Re-indexes every value association into a root with a new anchor and depth.
+    ///
+    /// Values which still fit keep their handles. Values which don't fit in the new root are
+    /// deleted, and returned in an iterator [`IntoIter<U, V>`].
+    ///
+    /// Fails with [`Error::Overflow`] if the new root can't be represented by `U`, in which case
+    /// the quadtree is unchanged.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(2);
+    /// let handle_a = qt.insert((1, 1), 'a').unwrap();
+    /// let handle_b = qt.insert((3, 3), 'b').unwrap();
+    ///
+    /// // Grow the tree from 4x4 to 16x16, shifted down and to the right.
+    /// let evicted: Vec<_> = qt.rebuild((2, 2).into(), 4).unwrap().collect();
+    /// assert_eq!(qt.anchor(), Point::from((2, 2)));
+    /// assert_eq!(qt.width(), 16);
+    ///
+    /// // (1, 1) no longer fits.
+    /// assert_eq!(evicted.len(), 1);
+    /// assert_eq!(evicted[0].handle(), handle_a);
+    /// assert_eq!(qt.get(handle_b).unwrap().value_ref(), &'b');
+    /// assert!(qt.insert((17, 17), 'c').is_some());
+    /// ```
+    ///
+    /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn rebuild(&mut self, anchor: Point<U>, depth: usize) -> Result<IntoIter<U, V>, Error> {
+        let inner = self.inner.try_reshaped(anchor, depth)?;
+        Ok(IntoIter {
+            entries: self.reindex(inner),
+        })
+    }
+
+    /// Re-indexes every value association into a root with a new depth, at the same anchor.
+    ///
+    /// Like [`.rebuild()`], values which no longer fit are deleted and returned.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    /// qt.insert((1, 1), 'a');
+    /// qt.insert((9, 9), 'b');
+    ///
+    /// let evicted: Vec<_> = qt.resize(3).unwrap().collect();
+    /// assert_eq!(qt.depth(), 3);
+    /// assert_eq!(evicted[0].value_ref(), &'b');
+    /// assert_eq!(qt.len(), 1);
+    /// ```
+    ///
+    /// [`.rebuild()`]: #method.rebuild
+    pub fn resize(&mut self, depth: usize) -> Result<IntoIter<U, V>, Error> {
+        self.rebuild(self.anchor(), depth)
+    }
+
     /// Given an handle, deletes a single item from the
     /// Quadtree. If that handle was found,
     /// `delete_by_handle()` returns an `Entry<U, V>`
@@ -478,50 +924,295 @@ where
         None
     }
 
-    // TODO(ambuc): Test this fn.
+    /// Moves the value with the given handle to a new region, without changing its handle.
+    ///
+    /// If successful, returns the region the value was previously associated with. Otherwise
+    /// returns [`Error::UnknownHandle`] if the handle wasn't found, or an error as per
+    /// [`.try_insert()`] if the new region is invalid or doesn't fit; the quadtree is untouched.
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// let region_a = Area::new((0,0), (2,2)).unwrap();
+    /// let handle = qt.insert(region_a, 'a').unwrap();
+    ///
+    /// let region_b = Area::new((9,9), (3,3)).unwrap();
+    /// assert_eq!(qt.move_to(handle, region_b), Ok(region_a));
+    ///
+    /// assert_eq!(qt.query(region_a).count(), 0);
+    /// let entry = qt.query(region_b).next().unwrap();
+    /// assert_eq!(entry.handle(), handle);
+    /// assert_eq!(entry.area(), region_b);
+    /// ```
+    ///
+    /// [`Error::UnknownHandle`]: error/enum.Error.html#variant.UnknownHandle
+    /// [`.try_insert()`]: #method.try_insert
+    pub fn move_to<A>(&mut self, handle: u64, area: A) -> Result<Area<U>, Error>
+    where
+        A: TryInto<Area<U>>,
+        Error: From<A::Error>,
+    {
+        let area = area.try_into()?;
+        if !self.contains(area) {
+            return Err(Error::OutOfBounds);
+        }
+        let entry = self
+            .store
+            .get_mut(&handle)
+            .ok_or(Error::UnknownHandle(handle))?;
+        let old = entry.area();
+        entry.set_area(area);
+        self.inner.move_handle(handle, old, area);
+        Ok(old)
+    }
+
+    /// Alias for [`.move_to()`] which shifts the value with the given handle by some offset.
+    ///
+    /// If the new region can't be represented by `U`, returns [`Error::Overflow`].
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<i32, char>::new_with_anchor((-8, -8).into(), 4);
+    ///
+    /// let region: Area<i32> = Area::new((0,0), (2,2)).unwrap();
+    /// let handle = qt.insert(region, 'a').unwrap();
+    ///
+    /// qt.translate(handle, -3, 1).unwrap();
+    /// assert_eq!(qt.get(handle).unwrap().anchor(), (-3, 1).into());
+    /// ```
+    ///
+    /// [`.move_to()`]: #method.move_to
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn translate(&mut self, handle: u64, dx: U, dy: U) -> Result<Area<U>, Error> {
+        let area = self
+            .store
+            .get(&handle)
+            .ok_or(Error::UnknownHandle(handle))?
+            .area()
+            .translated(dx, dy)?;
+        self.move_to(handle, area)
+    }
+
+    /// Shifts the region this quadtree represents, and every value association in it, by some
+    /// offset.
+    ///
+    /// Since every region moves together, nothing is re-inserted and handles are unchanged. If the
+    /// shifted root can't be represented by `U`, returns [`Error::Overflow`] and leaves the
+    /// quadtree unchanged.
+    /// ```
+    /// use quadtree_rs::{point::Point, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<i32, char>::new(4);
+    /// let handle = qt.insert((1, 2), 'a').unwrap();
+    ///
+    /// qt.translate_all(-8, 3).unwrap();
+    /// assert_eq!(qt.anchor(), Point::from((-8, 3)));
+    /// assert_eq!(qt.get(handle).unwrap().anchor(), (-7, 5).into());
+    /// assert_eq!(qt.query((-7, 5)).count(), 1);
+    /// ```
+    ///
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn translate_all(&mut self, dx: U, dy: U) -> Result<(), Error> {
+        // Every region in the tree lies within the root, so if the root fits, they all do.
+        self.inner.region().translated(dx, dy)?;
+
+        let offset = Point { x: dx, y: dy };
+        self.inner.translate(offset);
+        for entry in self.store.values_mut() {
+            let area = entry.area();
+            entry.set_area(Area::new_unchecked(
+                area.anchor() + offset,
+                area.dimensions(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scales the region this quadtree represents, and every value association in it, by some
+    /// positive factor.
+    ///
+    /// Anchors and dimensions are all multiplied by `factor`. The depth grows as needed to cover
+    /// the scaled root, and every value is re-inserted under its existing handle.
+    ///
+    /// Returns [`Error::InvalidDimensions`] if `factor` isn't positive, and [`Error::Overflow`] if
+    /// any scaled region can't be represented by `U`. Either way, the quadtree is unchanged.
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(2);
+    /// let handle = qt.insert(((1, 1), (2, 1)), 'a').unwrap();
+    ///
+    /// qt.scale_all(3).unwrap();
+    /// // 4x4 becomes 12x12, which needs a 16x16 root.
+    /// assert_eq!(qt.depth(), 4);
+    /// assert_eq!(qt.get(handle).unwrap().area(), Area::new((3, 3), (6, 3)).unwrap());
+    /// ```
+    ///
+    /// [`Error::InvalidDimensions`]: error/enum.Error.html#variant.InvalidDimensions
+    /// [`Error::Overflow`]: error/enum.Error.html#variant.Overflow
+    pub fn scale_all(&mut self, factor: U) -> Result<(), Error> {
+        if factor <= U::zero() {
+            return Err(Error::InvalidDimensions);
+        }
+        let inner = self
+            .inner
+            .try_reshaped_to_cover(self.inner.region().scaled(factor)?)?;
+        let scaled = self
+            .store
+            .values()
+            .map(|e| Ok((e.handle(), e.area().scaled(factor)?)))
+            .collect::<Result<Vec<(u64, Area<U>)>, Error>>()?;
+
+        for (handle, area) in scaled {
+            if let Some(entry) = self.store.get_mut(&handle) {
+                entry.set_area(area);
+            }
+        }
+        // The scaled root contains every scaled region, so nothing is evicted.
+        self.reindex(inner);
+        Ok(())
+    }
+
     /// Retains only the elements specified by the predicate.
     ///
-    /// In other words, remove all items such that `f(&mut v)` returns `false`.
-    pub fn retain<F>(&mut self, mut f: F) -> IntoIter<U, V>
+    /// In other words, remove all items such that `f(&mut v)` returns `false`. This follows the
+    /// semantics of [`HashMap::retain()`]; see [`.delete_by()`] for the inverse.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((1, 1), 2), ((2, 2), 3)]);
+    ///
+    /// qt.retain(|v| *v % 2 == 1);
+    ///
+    /// assert_eq!(qt.len(), 2);
+    /// assert_eq!(qt.query((1, 1)).count(), 0);
+    /// ```
+    ///
+    /// [`HashMap::retain()`]: https://doc.rust-lang.org/std/collections/struct.HashMap.html#method.retain
+    /// [`.delete_by()`]: #method.delete_by
+    pub fn retain<F>(&mut self, mut f: F)
     where
         F: FnMut(&mut V) -> bool,
-        U: Hash,
     {
-        // TODO(ambuc): I think this is technically correct but it seems to be interweaving three
-        // routines. Is there a way to simplify this?
-        let mut doomed: HashSet<(u64, Area<U>)> = HashSet::new();
-        for (handle, entry) in &mut self.store {
-            if f(entry.value_mut()) {
-                doomed.insert((*handle, entry.area()));
+        self.delete_by(|v| !f(v));
+    }
+
+    /// A variant of [`.retain()`] which only considers the elements intersecting some region.
+    /// Elements outside of the region are always retained.
+    ///
+    /// Rather than scanning every element in the quadtree, only the parts of the tree which
+    /// overlap the region are visited.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((1, 1), 2), ((9, 9), 4)]);
+    ///
+    /// qt.retain_in(((0, 0), (4, 4)), |v| *v % 2 == 1);
+    ///
+    /// // The 2 was dropped, but the 4 was outside of the region.
+    /// assert_eq!(qt.len(), 2);
+    /// assert_eq!(qt.query((9, 9)).count(), 1);
+    /// ```
+    ///
+    /// [`.retain()`]: #method.retain
+    pub fn retain_in<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F)
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        let area = match area.try_into() {
+            Ok(area) => area,
+            // An invalid region contains nothing, so there's nothing to remove.
+            Err(_) => return,
+        };
+        // The HandleIter only yields handles in subtrees which overlap the region, but those
+        // handles' own regions might not.
+        let candidates: Vec<u64> = HandleIter::new(&self.inner, area).collect();
+        let mut doomed: HashSet<u64> = HashSet::new();
+        for handle in candidates {
+            if let Some(entry) = self.store.get_mut(&handle) {
+                if entry.area().intersects(area) && !f(entry.value_mut()) {
+                    doomed.insert(handle);
+                }
             }
         }
-        // TODO(ambuc): There is an optimization here to do one traversal with many matches, over
-        // many traversals i.e. one per match.
-        let mut entries: Vec<Entry<U, V>> = vec![];
-        for (handle, region) in doomed {
-            entries.push(self.store.remove(&handle).unwrap());
-            self.inner.delete_by_handle(handle, region);
-        }
+        self.delete_handles_and_return(doomed);
+    }
 
-        IntoIter { entries }
+    /// Deletes all the elements specified by the predicate. The inverse of [`.retain()`].
+    ///
+    /// In other words, remove all items such that `f(&mut v)` returns `true`. Consumed
+    /// [`Entry<U, V>`] entries are returned in an iterator [`IntoIter<U, V>`].
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((1, 1), 2), ((2, 2), 3)]);
+    ///
+    /// let deleted: Vec<u8> = qt.delete_by(|v| *v % 2 == 1).map(|e| *e.value_ref()).collect();
+    ///
+    /// assert_eq!(deleted.len(), 2);
+    /// assert_eq!(qt.len(), 1);
+    /// ```
+    ///
+    /// [`.retain()`]: #method.retain
+    /// [`IntoIter<U, V>`]: iter/struct.IntoIter.html
+    /// [`Entry<U, V>`]: entry/struct.Entry.html
+    pub fn delete_by<F>(&mut self, mut f: F) -> IntoIter<U, V>
+    where
+        F: FnMut(&mut V) -> bool,
+    {
+        let doomed: HashSet<u64> = self
+            .store
+            .iter_mut()
+            .filter_map(|(handle, entry)| f(entry.value_mut()).then_some(*handle))
+            .collect();
+        self.delete_handles_and_return(doomed)
     }
-    // TODO(ambuc): retain_within
 
     /// Returns an iterator ([`Iter<U, V>`]) over all [`&'a Entry<U, V>`]
     /// region/value associations in the Quadtree.
     ///
     /// [`Iter<U, V>`]: iter/struct.Iter.html
     /// [`&'a Entry<U, V>`]: entry/struct.Entry.html
-    pub fn iter(&self) -> Iter<U, V> {
+    pub fn iter(&self) -> Iter<'_, U, V> {
         Iter::new(&self.inner, &self.store)
     }
 
+    /// Returns a mutable iterator ([`IterMut<U, V>`]) over all [`&'a mut Entry<U, V>`]
+    /// region/value associations in the Quadtree.
+    ///
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, u8>::new(4);
+    /// qt.extend(vec![((0, 0), 1), ((2, 3), 2)]);
+    ///
+    /// for entry in qt.iter_mut() {
+    ///     *entry.value_mut() *= 10;
+    /// }
+    ///
+    /// let mut values: Vec<u8> = qt.values().copied().collect();
+    /// values.sort();
+    /// assert_eq!(values, vec![10, 20]);
+    /// ```
+    ///
+    /// [`IterMut<U, V>`]: iter/struct.IterMut.html
+    /// [`&'a mut Entry<U, V>`]: entry/struct.Entry.html
+    pub fn iter_mut(&mut self) -> IterMut<'_, U, V> {
+        IterMut {
+            inner: self.store.values_mut(),
+        }
+    }
+
     /// Returns an iterator ([`Regions<U, V>`]) over all [`Area<U>`] regions
     /// in the Quadtree.
     ///
     /// [`Regions<U, V>`]: iter/struct.Regions.html
     /// [`Area<U>`]: area/struct.Area.html
-    pub fn regions(&self) -> Regions<U, V> {
+    pub fn regions(&self) -> Regions<'_, U, V> {
         Regions {
             inner: Iter::new(&self.inner, &self.store),
         }
@@ -531,27 +1222,105 @@ where
     /// Quadtree.
     ///
     /// [`Values<U, V>`]: iter/struct.Values.html
-    pub fn values(&self) -> Values<U, V> {
+    pub fn values(&self) -> Values<'_, U, V> {
         Values {
             inner: Iter::new(&self.inner, &self.store),
         }
     }
 
-    // fn
+    /// Returns a mutable iterator ([`ValuesMut<U, V>`]) over all `&'a mut V` values in the
+    /// Quadtree.
+    ///
+    /// [`ValuesMut<U, V>`]: iter/struct.ValuesMut.html
+    pub fn values_mut(&mut self) -> ValuesMut<'_, U, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
 
-    fn modify_region<F, M>(&mut self, filter: F, modify: M)
-    where
-        F: Fn(Area<U>) -> bool,
-        M: Fn(&mut V) + Copy,
-    {
-        let relevant_handles: Vec<u64> =
-            HandleIter::new(&self.inner, self.inner.region()).collect();
-        for i in relevant_handles {
-            if let Some(entry) = self.store.get_mut(&i) {
-                if filter(entry.area()) {
-                    modify(entry.value_mut());
-                }
-            }
+    /// Returns an iterator ([`IntersectingPairs<U, V>`]) over every unordered pair of entries
+    /// whose regions intersect one another.
+    ///
+    /// Each pair is returned exactly once, ordered by handle, in a single walk of the tree. This
+    /// is useful as a broad-phase for collision detection.
+    /// ```
+    /// use quadtree_rs::{area::Area, Quadtree};
+    ///
+    /// let mut qt = Quadtree::<u32, char>::new(4);
+    ///
+    /// let region_a: Area<u32> = Area::new((0,0), (3,3)).unwrap();
+    /// let handle_a = qt.insert(region_a, 'a').unwrap();
+    ///
+    /// let region_b: Area<u32> = Area::new((2,2), (3,3)).unwrap();
+    /// let handle_b = qt.insert(region_b, 'b').unwrap();
+    ///
+    /// // 'c' doesn't touch either of the others.
+    /// let region_c: Area<u32> = Area::new((8,8), (2,2)).unwrap();
+    /// qt.insert(region_c, 'c');
+    ///
+    /// let pairs: Vec<(u64, u64)> = qt
+    ///     .intersecting_pairs()
+    ///     .map(|(x, y)| (x.handle(), y.handle()))
+    ///     .collect();
+    /// assert_eq!(pairs, vec![(handle_a, handle_b)]);
+    /// ```
+    ///
+    /// [`IntersectingPairs<U, V>`]: iter/struct.IntersectingPairs.html
+    pub fn intersecting_pairs(&self) -> IntersectingPairs<'_, U, V> {
+        IntersectingPairs::new(&self.inner, &self.store)
+    }
+
+    // pub(crate)
+
+    pub(crate) fn inner(&self) -> &QTInner<U> {
+        &self.inner
+    }
+
+    // Every entry, in increasing order of handle. Handles are handed out in insertion order,
+    // so this is the order for output which should be stable across runs.
+    pub(crate) fn entries_by_handle(&self) -> Vec<&Entry<U, V>> {
+        let mut entries: Vec<&Entry<U, V>> = self.store.values().collect();
+        entries.sort_unstable_by_key(|e| e.handle());
+        entries
+    }
+
+    #[cfg(feature = "rayon")]
+    pub(crate) fn store(&self) -> &StoreType<U, V> {
+        &self.store
+    }
+
+    #[cfg(feature = "rayon")]
+    pub(crate) fn parts_mut(&mut self) -> (&mut QTInner<U>, &mut StoreType<U, V>) {
+        (&mut self.inner, &mut self.store)
+    }
+
+    // Files an existing entry under its own handle. Expects the entry's region to fit within
+    // the quadtree.
+    pub(crate) fn insert_entry(&mut self, entry: Entry<U, V>) {
+        self.inner.insert_entry(entry, &mut self.store);
+    }
+
+    pub(crate) fn from_parts(inner: QTInner<U>, store: StoreType<U, V>) -> Self {
+        Self { inner, store }
+    }
+
+    pub(crate) fn into_parts(self) -> (QTInner<U>, StoreType<U, V>) {
+        (self.inner, self.store)
+    }
+}
+
+/// Hashes the region this quadtree represents and each of its entries, in handle order. Two
+/// equal quadtrees hold the same entries over the same region, and so hash identically.
+impl<U, V> Hash for Quadtree<U, V>
+where
+    U: PrimInt + Default + Hash,
+    V: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inner.region().hash(state);
+        self.inner.depth().hash(state);
+        for entry in self.entries_by_handle() {
+            entry.hash(state);
         }
     }
 }
@@ -587,6 +1356,19 @@ where
     }
 }
 
+// Mutable iterator for the Quadtree, returning by-mutable-reference.
+impl<'a, U, V> IntoIterator for &'a mut Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    type Item = &'a mut Entry<U, V>;
+    type IntoIter = IterMut<'a, U, V>;
+
+    fn into_iter(self) -> IterMut<'a, U, V> {
+        self.iter_mut()
+    }
+}
+
 impl<U, V> IntoIterator for Quadtree<U, V>
 where
     U: PrimInt + Default,
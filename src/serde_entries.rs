@@ -0,0 +1,161 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A serde representation of a [`Quadtree`] which holds only its root geometry and its entries.
+//!
+//! The derived `Serialize` and `Deserialize` implementations on [`Quadtree`] write out its
+//! internal node layout, and trust whatever layout they read back. This module instead writes
+//! the anchor, depth, handle counter, and a list of entries, and on deserialization re-inserts
+//! each entry under its handle. Every entry is validated along the way, so the rebuilt tree is
+//! always consistent.
+//!
+//! Use it with `#[serde(with = "quadtree_rs::serde_entries")]` on a field, or call
+//! [`serialize`] and [`deserialize`] directly.
+//! ```
+//! use quadtree_rs::Quadtree;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Map {
+//!     #[serde(with = "quadtree_rs::serde_entries")]
+//!     labels: Quadtree<u32, String>,
+//! }
+//!
+//! let mut labels = Quadtree::<u32, String>::new(4);
+//! let handle = labels.insert(((1, 1), (2, 2)), "lake".to_string()).unwrap();
+//!
+//! let json = serde_json::to_string(&Map { labels }).unwrap();
+//! let map: Map = serde_json::from_str(&json).unwrap();
+//! assert_eq!(map.labels.get(handle).unwrap().value_ref(), "lake");
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`serialize`]: fn.serialize.html
+//! [`deserialize`]: fn.deserialize.html
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+    types::StoreType,
+};
+use num::PrimInt;
+use serde::{
+    de,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::default::Default;
+
+#[derive(Serialize)]
+struct QuadtreeRef<'a, U, V> {
+    anchor: Point<U>,
+    depth: usize,
+    handle_counter: u64,
+    entries: Vec<EntryRef<'a, U, V>>,
+}
+
+#[derive(Serialize)]
+struct EntryRef<'a, U, V> {
+    handle: u64,
+    anchor: Point<U>,
+    dimensions: (U, U),
+    value: &'a V,
+}
+
+#[derive(Deserialize)]
+struct QuadtreeRepr<U, V> {
+    anchor: Point<U>,
+    depth: usize,
+    handle_counter: u64,
+    entries: Vec<EntryRepr<U, V>>,
+}
+
+#[derive(Deserialize)]
+struct EntryRepr<U, V> {
+    handle: u64,
+    anchor: Point<U>,
+    dimensions: (U, U),
+    value: V,
+}
+
+/// Serializes the quadtree's root geometry and entries, in handle order.
+pub fn serialize<U, V, S>(qt: &Quadtree<U, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    U: PrimInt + Default + Serialize,
+    V: Serialize,
+    S: Serializer,
+{
+    let entries: Vec<EntryRef<U, V>> = qt
+        .entries_by_handle()
+        .into_iter()
+        .map(|e| EntryRef {
+            handle: e.handle(),
+            anchor: e.anchor(),
+            dimensions: (e.width(), e.height()),
+            value: e.value_ref(),
+        })
+        .collect();
+
+    QuadtreeRef {
+        anchor: qt.anchor(),
+        depth: qt.depth(),
+        handle_counter: qt.inner().handle_counter(),
+        entries,
+    }
+    .serialize(serializer)
+}
+
+/// Deserializes a quadtree written by [`serialize`], re-inserting every entry under its handle.
+///
+/// Fails if the root can't be represented by `U`, or if any entry has an invalid region, a
+/// region which doesn't fit in the root, a repeated handle, or a handle no less than the handle
+/// counter.
+///
+/// [`serialize`]: fn.serialize.html
+pub fn deserialize<'de, U, V, D>(deserializer: D) -> Result<Quadtree<U, V>, D::Error>
+where
+    U: PrimInt + Default + Deserialize<'de>,
+    V: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let repr = QuadtreeRepr::<U, V>::deserialize(deserializer)?;
+
+    let mut inner = QTInner::try_new(repr.anchor, repr.depth).map_err(de::Error::custom)?;
+    let mut store: StoreType<U, V> = StoreType::new();
+    for entry in repr.entries {
+        let handle = entry.handle;
+        let invalid = |e: Error| de::Error::custom(format_args!("entry {}: {}", handle, e));
+
+        let region = Area::new(entry.anchor, entry.dimensions).map_err(invalid)?;
+        if !inner.region().contains(region) {
+            return Err(invalid(Error::OutOfBounds));
+        }
+        if handle >= repr.handle_counter || store.contains_key(&handle) {
+            return Err(de::Error::custom(format_args!(
+                "entry {}: handle is repeated, or wasn't handed out",
+                handle
+            )));
+        }
+        inner.insert_entry(Entry::new((region, entry.value), handle), &mut store);
+    }
+    inner.set_handle_counter(repr.handle_counter);
+
+    Ok(Quadtree::from_parts(inner, store))
+}
@@ -0,0 +1,235 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! SVG rendering of a [`Quadtree`]'s subdivision and entries, for debugging.
+//!
+//! The drawing has four layers, each an SVG group with a `class`, from bottom to top:
+//!   - `nodes`: the region of every node which has been subdivided into, as a grey grid;
+//!   - `entries`: the region of every entry, filled with a colour picked by a callback;
+//!   - `results`: the entries returned by a query of the highlighted area, outlined;
+//!   - `highlight`: the highlighted area itself, dashed.
+//!
+//! The anchor of the quadtree is drawn at the top-left corner of the image, and each unit is
+//! `scale` pixels wide.
+//! ```
+//! use quadtree_rs::Quadtree;
+//!
+//! let mut qt = Quadtree::<u32, &str>::new(3);
+//! qt.insert(((0, 0), (2, 2)), "lake");
+//! qt.insert((5, 6), "well");
+//!
+//! let svg = qt
+//!     .svg()
+//!     .scale(10)
+//!     .fill(|v: &&str| if *v == "lake" { "blue" } else { "brown" }.to_string())
+//!     .highlight(((4, 4), (4, 4)))
+//!     .render();
+//! assert!(svg.starts_with("<svg "));
+//! assert!(svg.contains(r#"<rect x="50" y="60" width="10" height="10" fill="brown">"#));
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::{
+    convert::TryInto,
+    default::Default,
+    fmt,
+};
+
+/// A configurable SVG rendering of a [`Quadtree`]. Returned by [`Quadtree::svg()`].
+///
+/// Nothing is drawn until [`.render()`] is called, or the renderer is formatted with `{}`.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`Quadtree::svg()`]: ../struct.Quadtree.html#method.svg
+/// [`.render()`]: #method.render
+pub struct Svg<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a Quadtree<U, V>,
+    scale: u32,
+    fill: Box<dyn Fn(&V) -> String + 'a>,
+    highlight: Option<Area<U>>,
+}
+
+impl<'a, U, V> Svg<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Sets the width and height, in pixels, of one unit. Defaults to `16`.
+    pub fn scale(mut self, scale: u32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets the callback which picks the fill colour of each entry from its value. The colour
+    /// may be anything SVG accepts, e.g. `"red"` or `"#ff0000"`. Defaults to `"steelblue"` for
+    /// every entry.
+    pub fn fill<F>(mut self, fill: F) -> Self
+    where
+        F: Fn(&V) -> String + 'a,
+    {
+        self.fill = Box::new(fill);
+        self
+    }
+
+    /// Highlights an area, and outlines the entries which a [`.query()`] of it would return.
+    ///
+    /// If the area is invalid, nothing is highlighted.
+    ///
+    /// [`.query()`]: ../struct.Quadtree.html#method.query
+    pub fn highlight(mut self, area: impl TryInto<Area<U>>) -> Self {
+        self.highlight = area.try_into().ok();
+        self
+    }
+
+    /// Writes the SVG document.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    // fn
+
+    // The pixel rectangle covering @area, relative to the anchor of the quadtree.
+    fn rect(&self, area: Area<U>) -> String {
+        let anchor = self.qt.anchor();
+        let px = |v: U, origin: U| {
+            (v.to_f64().unwrap_or(0.0) - origin.to_f64().unwrap_or(0.0)) * f64::from(self.scale)
+        };
+        format!(
+            r#"x="{}" y="{}" width="{}" height="{}""#,
+            px(area.left_edge(), anchor.x),
+            px(area.top_edge(), anchor.y),
+            px(area.width(), U::zero()),
+            px(area.height(), U::zero()),
+        )
+    }
+
+    fn write_nodes(&self, f: &mut fmt::Formatter, qt: &QTInner<U>) -> fmt::Result {
+        writeln!(f, "    <rect {}/>", self.rect(qt.region()))?;
+        if let Some(sqs) = qt.subquadrants() {
+            for sq in sqs.iter() {
+                self.write_nodes(f, sq)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<U, V> fmt::Display for Svg<'_, U, V>
+where
+    U: PrimInt + Default,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let side = self.qt.width() as f64 * f64::from(self.scale);
+        writeln!(
+            f,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{0}" viewBox="0 0 {0} {0}">"#,
+            side
+        )?;
+
+        writeln!(
+            f,
+            r##"  <g class="nodes" fill="none" stroke="#bbb" stroke-width="1">"##
+        )?;
+        self.write_nodes(f, self.qt.inner())?;
+        writeln!(f, "  </g>")?;
+
+        writeln!(
+            f,
+            r#"  <g class="entries" fill-opacity="0.5" stroke="black" stroke-width="1">"#
+        )?;
+        for entry in self.qt.entries_by_handle() {
+            writeln!(
+                f,
+                r#"    <rect {} fill="{}"><title>{}</title></rect>"#,
+                self.rect(entry.area()),
+                escape(&(self.fill)(entry.value_ref())),
+                entry.handle(),
+            )?;
+        }
+        writeln!(f, "  </g>")?;
+
+        if let Some(area) = self.highlight {
+            writeln!(
+                f,
+                r#"  <g class="results" fill="none" stroke="orange" stroke-width="3">"#
+            )?;
+            let mut results: Vec<&Entry<U, V>> = self.qt.query(area).collect();
+            results.sort_unstable_by_key(|e| e.handle());
+            for entry in results {
+                writeln!(f, "    <rect {}/>", self.rect(entry.area()))?;
+            }
+            writeln!(f, "  </g>")?;
+            writeln!(
+                f,
+                r#"  <g class="highlight" fill="none" stroke="red" stroke-width="2" stroke-dasharray="4">"#
+            )?;
+            writeln!(f, "    <rect {}/>", self.rect(area))?;
+            writeln!(f, "  </g>")?;
+        }
+
+        writeln!(f, "</svg>")
+    }
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Returns an SVG renderer for this quadtree. See the [`svg`] module.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u8, ()>::new(1);
+    /// qt.insert((0, 0), ());
+    ///
+    /// // The root and its four subquadrants, then the entry.
+    /// let svg = qt.svg().render();
+    /// assert_eq!(svg.matches("<rect ").count(), 5 + 1);
+    /// ```
+    ///
+    /// [`svg`]: svg/index.html
+    pub fn svg(&self) -> Svg<'_, U, V> {
+        Svg {
+            qt: self,
+            scale: 16,
+            fill: Box::new(|_| "steelblue".to_string()),
+            highlight: None,
+        }
+    }
+}
+
+// Escapes @s for use in a double-quoted XML attribute.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
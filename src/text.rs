@@ -0,0 +1,219 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Plain-text rendering of a [`Quadtree`], for debugging in a terminal.
+//!
+//! There are two views:
+//!   - [`.boxes()`] draws how the tree has been subdivided, as an ASCII box diagram;
+//!   - [`.heat()`] shades each cell by how many entries overlap it, with Unicode block
+//!     characters.
+//!
+//! Both views divide the quadtree into square cells, each `scale` units wide, and draw one
+//! cell per character (or per box, in the box diagram). Rows run from the anchor's `y`
+//! downwards, and columns from the anchor's `x` rightwards. By default a view is at most 64
+//! cells wide, however deep the tree.
+//! ```
+//! use quadtree_rs::Quadtree;
+//!
+//! let mut qt = Quadtree::<u8, char>::new(2);
+//! qt.insert((0, 0), 'a');
+//! qt.insert(((0, 0), (2, 2)), 'b');
+//!
+//! assert_eq!(
+//!     qt.text().boxes(),
+//!     "\
+//! +---+---+---+---+
+//! |   |   |       |
+//! +---+---+       +
+//! |   |   |       |
+//! +---+---+---+---+
+//! |       |       |
+//! +       +       +
+//! |       |       |
+//! +---+---+---+---+
+//! "
+//! );
+//! assert_eq!(
+//!     qt.text().heat(),
+//!     "\
+//! ┌────┐
+//! │▒░  │
+//! │░░  │
+//! │    │
+//! │    │
+//! └────┘
+//! "
+//! );
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`.boxes()`]: struct.Text.html#method.boxes
+//! [`.heat()`]: struct.Text.html#method.heat
+
+use crate::{
+    area::Area,
+    point::Point,
+    qtinner::QTInner,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::default::Default;
+
+// The most cells drawn along each side of the quadtree, unless a scale is set.
+const MAX_CELLS: usize = 64;
+
+// The shade for a cell overlapped by 0, 1, 2, 3, or 4+ entries.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// A plain-text rendering of a [`Quadtree`]. Returned by [`Quadtree::text()`].
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`Quadtree::text()`]: ../struct.Quadtree.html#method.text
+pub struct Text<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: &'a Quadtree<U, V>,
+    scale: usize,
+}
+
+impl<'a, U, V> Text<'a, U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Sets the width and height, in units, of one cell. It's rounded up to a power of two so
+    /// that cells line up with nodes, and is at most the width of the quadtree.
+    ///
+    /// Defaults to `1`, or for quadtrees wider than 64 units, to the scale which draws 64 cells
+    /// along each side. Set a smaller scale to draw a larger tree in full.
+    ///
+    /// In the box diagram, nodes narrower than a cell aren't drawn.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1).next_power_of_two().min(self.qt.width());
+        self
+    }
+
+    /// Draws the region of every node in the tree as an ASCII box, in the style of the diagrams
+    /// in the crate documentation. Each cell is three characters wide and one line tall, and `+`
+    /// marks every cell corner which lies on the edge of a node.
+    pub fn boxes(&self) -> String {
+        let n = self.cells();
+        let mut grid = vec![vec![' '; 4 * n + 1]; 2 * n + 1];
+        self.draw_node(&mut grid, self.qt.inner());
+        grid.into_iter()
+            .map(|row| row.into_iter().chain(Some('\n')).collect::<String>())
+            .collect()
+    }
+
+    /// Shades each cell by the number of entries which overlap it: ` `, `░`, `▒`, `▓`, or, for
+    /// four or more, `█`. The view is framed with box-drawing characters.
+    pub fn heat(&self) -> String {
+        let n = self.cells();
+        let mut s = format!("┌{}┐\n", "─".repeat(n));
+        for row in 0..n {
+            s.push('│');
+            for col in 0..n {
+                let count = self
+                    .cell_area(col, row)
+                    .map_or(0, |a| self.qt.query(a).count());
+                s.push(SHADES[count.min(SHADES.len() - 1)]);
+            }
+            s.push_str("│\n");
+        }
+        s.push_str(&format!("└{}┘\n", "─".repeat(n)));
+        s
+    }
+
+    // fn
+
+    // The number of cells along each side of the quadtree.
+    fn cells(&self) -> usize {
+        self.qt.width() / self.scale
+    }
+
+    // The region of the cell at (@col, @row).
+    fn cell_area(&self, col: usize, row: usize) -> Option<Area<U>> {
+        let anchor = self.qt.anchor();
+        let side = U::from(self.scale)?;
+        Area::new(
+            Point {
+                x: anchor.x + U::from(col * self.scale)?,
+                y: anchor.y + U::from(row * self.scale)?,
+            },
+            (side, side),
+        )
+        .ok()
+    }
+
+    // The cell boundary @v lies on, counting from @origin.
+    fn boundary(&self, v: U, origin: U) -> usize {
+        (v - origin).to_usize().unwrap_or(0) / self.scale
+    }
+
+    fn draw_node(&self, grid: &mut [Vec<char>], qt: &QTInner<U>) {
+        let region = qt.region();
+        if region.width().to_usize().unwrap_or(0) < self.scale {
+            return;
+        }
+
+        let anchor = self.qt.anchor();
+        let (l, r) = (
+            self.boundary(region.left_edge(), anchor.x),
+            self.boundary(region.right_edge(), anchor.x),
+        );
+        let (t, b) = (
+            self.boundary(region.top_edge(), anchor.y),
+            self.boundary(region.bottom_edge(), anchor.y),
+        );
+        for col in l..r {
+            for line in &[2 * t, 2 * b] {
+                grid[*line][4 * col + 1..4 * col + 4].copy_from_slice(&['-'; 3]);
+            }
+        }
+        for row in t..b {
+            for column in &[4 * l, 4 * r] {
+                grid[2 * row + 1][*column] = '|';
+            }
+        }
+        for col in l..=r {
+            grid[2 * t][4 * col] = '+';
+            grid[2 * b][4 * col] = '+';
+        }
+        for row in t..=b {
+            grid[2 * row][4 * l] = '+';
+            grid[2 * row][4 * r] = '+';
+        }
+
+        if let Some(sqs) = qt.subquadrants() {
+            for sq in sqs.iter() {
+                self.draw_node(grid, sq);
+            }
+        }
+    }
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    /// Returns a plain-text renderer for this quadtree. See the [`text`] module.
+    ///
+    /// [`text`]: text/index.html
+    pub fn text(&self) -> Text<'_, U, V> {
+        Text {
+            qt: self,
+            scale: (self.width() / MAX_CELLS).max(1),
+        }
+    }
+}
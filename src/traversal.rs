@@ -16,9 +16,14 @@ use crate::area::Area;
 use num::PrimInt;
 use std::default::Default;
 
+/// How an operation over some region of a [`Quadtree`] decides which entries it applies to.
+///
+/// [`Quadtree`]: struct.Quadtree.html
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub(crate) enum Traversal {
+pub enum Traversal {
+    /// Apply to every entry whose region _intersects_ the operational region.
     Overlapping,
+    /// Apply only to entries whose regions are _totally contained by_ the operational region.
     Strict,
 }
 
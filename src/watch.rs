@@ -0,0 +1,344 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Watches on regions of a [`Quadtree`], which report changes to the entries over them.
+//!
+//! A [`WatchedQuadtree`] wraps a quadtree. Each [`.watch()`] registers a region, and every
+//! [`.insert()`], [`.delete_by_handle()`], [`.modify()`], [`.move_to()`], [`.translate()`], and
+//! [`.reset()`] made through the wrapper queues an [`Event`] for each watch whose region the
+//! affected entry overlaps. Moves are reported as an entry entering, leaving, or moving within
+//! a watched region. Events wait in the queue until [`.drain_events()`] is called, e.g. once per
+//! tick.
+//!
+//! Watches are themselves kept in a quadtree, so a change only looks at the watches near it.
+//! ```
+//! use quadtree_rs::{
+//!     area::Area,
+//!     watch::{EventKind, WatchedQuadtree},
+//!     Quadtree,
+//! };
+//!
+//! let mut qt = WatchedQuadtree::new(Quadtree::<u32, &str>::new(4));
+//! let room = qt.watch(((0, 0), (4, 4))).unwrap();
+//!
+//! let outside = Area::new((8, 8), (1, 1)).unwrap();
+//! let inside = Area::new((2, 2), (1, 1)).unwrap();
+//! let player = qt.insert(outside, "player").unwrap();
+//! qt.move_to(player, inside).unwrap();
+//! qt.delete_by_handle(player);
+//!
+//! // The insertion was outside the room, so only the move and the deletion are reported.
+//! let events: Vec<_> = qt
+//!     .drain_events()
+//!     .map(|e| (e.watch(), e.handle(), e.kind()))
+//!     .collect();
+//! assert_eq!(
+//!     events,
+//!     vec![
+//!         (room, player, EventKind::Entered { from: outside, to: inside }),
+//!         (room, player, EventKind::Removed(inside)),
+//!     ]
+//! );
+//! ```
+//!
+//! [`Quadtree`]: ../struct.Quadtree.html
+//! [`WatchedQuadtree`]: struct.WatchedQuadtree.html
+//! [`Event`]: struct.Event.html
+//! [`.watch()`]: struct.WatchedQuadtree.html#method.watch
+//! [`.insert()`]: struct.WatchedQuadtree.html#method.insert
+//! [`.delete_by_handle()`]: struct.WatchedQuadtree.html#method.delete_by_handle
+//! [`.modify()`]: struct.WatchedQuadtree.html#method.modify
+//! [`.move_to()`]: struct.WatchedQuadtree.html#method.move_to
+//! [`.translate()`]: struct.WatchedQuadtree.html#method.translate
+//! [`.reset()`]: struct.WatchedQuadtree.html#method.reset
+//! [`.drain_events()`]: struct.WatchedQuadtree.html#method.drain_events
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::{
+    convert::TryInto,
+    default::Default,
+    ops::Deref,
+    vec::Drain,
+};
+
+/// A change to an entry over a watched region. Returned by [`.drain_events()`].
+///
+/// [`.drain_events()`]: struct.WatchedQuadtree.html#method.drain_events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Event<U>
+where
+    U: PrimInt + Default,
+{
+    watch: u64,
+    handle: u64,
+    kind: EventKind<U>,
+}
+
+impl<U> Event<U>
+where
+    U: PrimInt + Default,
+{
+    /// The id of the watch, as returned by [`.watch()`].
+    ///
+    /// [`.watch()`]: struct.WatchedQuadtree.html#method.watch
+    pub fn watch(&self) -> u64 {
+        self.watch
+    }
+
+    /// The handle of the entry which changed.
+    pub fn handle(&self) -> u64 {
+        self.handle
+    }
+
+    /// What happened to the entry.
+    pub fn kind(&self) -> EventKind<U> {
+        self.kind
+    }
+}
+
+/// What happened to an entry over a watched region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventKind<U>
+where
+    U: PrimInt + Default,
+{
+    /// The entry was inserted over this region.
+    Inserted(Area<U>),
+    /// The entry was deleted from this region.
+    Removed(Area<U>),
+    /// The entry, over this region, had its value modified.
+    Modified(Area<U>),
+    /// The entry moved onto the watched region from somewhere outside it.
+    Entered {
+        /// The entry's region before the move.
+        from: Area<U>,
+        /// The entry's region after the move.
+        to: Area<U>,
+    },
+    /// The entry moved off the watched region.
+    Left {
+        /// The entry's region before the move.
+        from: Area<U>,
+        /// The entry's region after the move.
+        to: Area<U>,
+    },
+    /// The entry moved, and overlaps the watched region both before and after.
+    Moved {
+        /// The entry's region before the move.
+        from: Area<U>,
+        /// The entry's region after the move.
+        to: Area<U>,
+    },
+}
+
+/// A [`Quadtree`] which reports changes to the entries over watched regions. See the [`watch`]
+/// module.
+///
+/// Derefs to the wrapped quadtree, so all of its read-only methods are available. Changes must
+/// be made through the methods here to be reported.
+///
+/// [`Quadtree`]: ../struct.Quadtree.html
+/// [`watch`]: index.html
+pub struct WatchedQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    qt: Quadtree<U, V>,
+    // The watched regions. Their handles are the watch ids.
+    watches: Quadtree<U, ()>,
+    events: Vec<Event<U>>,
+}
+
+impl<U, V> WatchedQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    // pub
+
+    /// Wraps `qt`, with no watches.
+    pub fn new(qt: Quadtree<U, V>) -> Self {
+        let watches = Quadtree::new_with_anchor(qt.anchor(), qt.depth());
+        WatchedQuadtree {
+            qt,
+            watches,
+            events: Vec::new(),
+        }
+    }
+
+    /// Stops watching, and returns the quadtree. Undrained events are dropped.
+    pub fn into_inner(self) -> Quadtree<U, V> {
+        self.qt
+    }
+
+    /// Starts watching a region, and returns the watch's id. Entries already over the region
+    /// aren't reported.
+    ///
+    /// Returns `None` if the region is invalid or doesn't fit in the quadtree.
+    pub fn watch(&mut self, area: impl TryInto<Area<U>>) -> Option<u64> {
+        self.watches.insert(area, ())
+    }
+
+    /// Stops the watch with the given id, and returns the region it watched. Its events already
+    /// in the queue are kept.
+    pub fn unwatch(&mut self, watch: u64) -> Option<Area<U>> {
+        self.watches.delete_by_handle(watch).map(|w| w.area())
+    }
+
+    /// The region watched by the watch with the given id, if there is one.
+    pub fn watched(&self, watch: u64) -> Option<Area<U>> {
+        self.watches.get(watch).map(|w| w.area())
+    }
+
+    /// The queued events, oldest first.
+    pub fn events(&self) -> &[Event<U>] {
+        &self.events
+    }
+
+    /// Removes and returns the queued events, oldest first. The events from a single change are
+    /// ordered by watch id.
+    pub fn drain_events(&mut self) -> Drain<'_, Event<U>> {
+        self.events.drain(..)
+    }
+
+    /// Reports a [`Quadtree::insert()`].
+    ///
+    /// [`Quadtree::insert()`]: ../struct.Quadtree.html#method.insert
+    pub fn insert(&mut self, region: impl TryInto<Area<U>>, val: V) -> Option<u64> {
+        let region = region.try_into().ok()?;
+        let handle = self.qt.insert(region, val)?;
+        self.notify(handle, region, EventKind::Inserted(region));
+        Some(handle)
+    }
+
+    /// Reports a [`Quadtree::delete_by_handle()`].
+    ///
+    /// [`Quadtree::delete_by_handle()`]: ../struct.Quadtree.html#method.delete_by_handle
+    pub fn delete_by_handle(&mut self, handle: u64) -> Option<Entry<U, V>> {
+        let entry = self.qt.delete_by_handle(handle)?;
+        self.notify(handle, entry.area(), EventKind::Removed(entry.area()));
+        Some(entry)
+    }
+
+    /// Reports a [`Quadtree::modify()`], as a modification of each entry in the area.
+    ///
+    /// [`Quadtree::modify()`]: ../struct.Quadtree.html#method.modify
+    pub fn modify<F>(&mut self, area: impl TryInto<Area<U>>, mut f: F)
+    where
+        F: FnMut(&mut V),
+    {
+        let mut changed = Vec::new();
+        for entry in self.qt.query_mut(area) {
+            f(entry.value_mut());
+            changed.push((entry.handle(), entry.area()));
+        }
+        changed.sort_unstable_by_key(|(handle, _)| *handle);
+        for (handle, region) in changed {
+            self.notify(handle, region, EventKind::Modified(region));
+        }
+    }
+
+    /// Reports a [`Quadtree::move_to()`].
+    ///
+    /// [`Quadtree::move_to()`]: ../struct.Quadtree.html#method.move_to
+    pub fn move_to<A>(&mut self, handle: u64, area: A) -> Result<Area<U>, Error>
+    where
+        A: TryInto<Area<U>>,
+        Error: From<A::Error>,
+    {
+        let to = area.try_into()?;
+        let from = self.qt.move_to::<Area<U>>(handle, to)?;
+        self.notify_move(handle, from, to);
+        Ok(from)
+    }
+
+    /// Reports a [`Quadtree::translate()`].
+    ///
+    /// [`Quadtree::translate()`]: ../struct.Quadtree.html#method.translate
+    pub fn translate(&mut self, handle: u64, dx: U, dy: U) -> Result<Area<U>, Error> {
+        let from = self.qt.translate(handle, dx, dy)?;
+        let to = from.translated(dx, dy)?;
+        self.notify_move(handle, from, to);
+        Ok(from)
+    }
+
+    /// Reports a [`Quadtree::reset()`], as the removal of every entry. Watches are kept.
+    ///
+    /// [`Quadtree::reset()`]: ../struct.Quadtree.html#method.reset
+    pub fn reset(&mut self) {
+        let removed: Vec<(u64, Area<U>)> = self
+            .qt
+            .entries_by_handle()
+            .into_iter()
+            .map(|e| (e.handle(), e.area()))
+            .collect();
+        self.qt.reset();
+        for (handle, region) in removed {
+            self.notify(handle, region, EventKind::Removed(region));
+        }
+    }
+
+    // fn
+
+    // Queues an event of @kind about @handle, over @region, for every watch overlapping it.
+    fn notify(&mut self, handle: u64, region: Area<U>, kind: EventKind<U>) {
+        let mut watches: Vec<u64> = self.watches.query(region).map(|w| w.handle()).collect();
+        watches.sort_unstable();
+        self.events.extend(watches.into_iter().map(|watch| Event {
+            watch,
+            handle,
+            kind,
+        }));
+    }
+
+    // Queues an event about @handle moving from @from to @to, for every watch overlapping
+    // either.
+    fn notify_move(&mut self, handle: u64, from: Area<U>, to: Area<U>) {
+        let mut watches: Vec<(u64, Area<U>)> = self
+            .watches
+            .query(from)
+            .chain(self.watches.query(to))
+            .map(|w| (w.handle(), w.area()))
+            .collect();
+        watches.sort_unstable_by_key(|(watch, _)| *watch);
+        watches.dedup_by_key(|(watch, _)| *watch);
+        self.events
+            .extend(watches.into_iter().map(|(watch, area)| Event {
+                watch,
+                handle,
+                kind: match (area.intersects(from), area.intersects(to)) {
+                    (false, _) => EventKind::Entered { from, to },
+                    (true, false) => EventKind::Left { from, to },
+                    (true, true) => EventKind::Moved { from, to },
+                },
+            }));
+    }
+}
+
+impl<U, V> Deref for WatchedQuadtree<U, V>
+where
+    U: PrimInt + Default,
+{
+    type Target = Quadtree<U, V>;
+
+    fn deref(&self) -> &Quadtree<U, V> {
+        &self.qt
+    }
+}
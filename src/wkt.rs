@@ -0,0 +1,238 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! [Well-Known Text](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry)
+//! for points, regions, and whole quadtrees.
+//!
+//! A [`Point`] is written as a `POINT`, and an [`Area`] as a closed, rectangular `POLYGON`. Both
+//! are parsed back with [`str::parse()`].
+//! ```
+//! use quadtree_rs::{area::Area, point::Point};
+//!
+//! let area: Area<i32> = Area::new((-1, 2), (3, 4)).unwrap();
+//! assert_eq!(area.to_string(), "POLYGON ((-1 2, 2 2, 2 6, -1 6, -1 2))");
+//! assert_eq!(area.to_string().parse::<Area<i32>>(), Ok(area));
+//!
+//! let point: Point<u8> = "POINT (3 4)".parse().unwrap();
+//! assert_eq!(point, Point { x: 3, y: 4 });
+//! ```
+//!
+//! [`Point`]: ../point/struct.Point.html
+//! [`Area`]: ../area/struct.Area.html
+//! [`str::parse()`]: https://doc.rust-lang.org/std/primitive.str.html#method.parse
+
+use crate::{
+    area::Area,
+    entry::Entry,
+    error::Error,
+    point::Point,
+    quadtree::Quadtree,
+};
+use num::PrimInt;
+use std::{
+    default::Default,
+    fmt,
+    str::FromStr,
+};
+
+/// The error returned when text can't be parsed as a [`Point`] or an [`Area`].
+///
+/// [`Point`]: ../point/struct.Point.html
+/// [`Area`]: ../area/struct.Area.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseWktError {
+    /// The text isn't a WKT `POINT` or `POLYGON` with integer coordinates, as appropriate.
+    Syntax,
+    /// The polygon isn't a closed, axis-aligned rectangle.
+    NotARectangle,
+    /// The rectangle isn't a valid region.
+    Area(Error),
+}
+
+impl fmt::Display for ParseWktError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseWktError::Syntax => write!(f, "invalid WKT"),
+            ParseWktError::NotARectangle => write!(f, "polygon is not an axis-aligned rectangle"),
+            ParseWktError::Area(e) => write!(f, "invalid region: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParseWktError {}
+
+impl<U> fmt::Display for Point<U>
+where
+    U: PrimInt + fmt::Display,
+{
+    /// Writes the point as a WKT `POINT`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "POINT ({} {})", self.x, self.y)
+    }
+}
+
+impl<U> fmt::Display for Area<U>
+where
+    U: PrimInt + Default + fmt::Display,
+{
+    /// Writes the region as a WKT `POLYGON`, clockwise from its anchor.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (l, t, r, b) = (
+            self.left_edge(),
+            self.top_edge(),
+            self.right_edge(),
+            self.bottom_edge(),
+        );
+        write!(
+            f,
+            "POLYGON (({} {}, {} {}, {} {}, {} {}, {} {}))",
+            l, t, r, t, r, b, l, b, l, t
+        )
+    }
+}
+
+impl<U> FromStr for Point<U>
+where
+    U: PrimInt + FromStr,
+{
+    type Err = ParseWktError;
+
+    /// Parses a WKT `POINT`. The keyword is case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_position(parenthesized(keyword(s, "POINT")?)?)
+    }
+}
+
+impl<U> FromStr for Area<U>
+where
+    U: PrimInt + Default + FromStr,
+{
+    type Err = ParseWktError;
+
+    /// Parses a WKT `POLYGON` with a single, closed ring of five positions which trace an
+    /// axis-aligned rectangle, in either direction from any corner. The keyword is
+    /// case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ring = parenthesized(parenthesized(keyword(s, "POLYGON")?)?)?;
+        let ring = ring
+            .split(',')
+            .map(parse_position)
+            .collect::<Result<Vec<Point<U>>, ParseWktError>>()?;
+        if ring.len() != 5 || ring[0] != ring[4] {
+            return Err(ParseWktError::NotARectangle);
+        }
+
+        let (min_x, max_x) = min_max(ring.iter().map(|p| p.x));
+        let (min_y, max_y) = min_max(ring.iter().map(|p| p.y));
+        let is_corner =
+            |p: &Point<U>| (p.x == min_x || p.x == max_x) && (p.y == min_y || p.y == max_y);
+        // Each side must be axis-aligned, and there must be four distinct corners.
+        let is_side = |w: &[Point<U>]| (w[0].x == w[1].x) != (w[0].y == w[1].y);
+        if !ring.iter().all(is_corner)
+            || !ring.windows(2).all(is_side)
+            || ring[0] == ring[2]
+            || ring[1] == ring[3]
+        {
+            return Err(ParseWktError::NotARectangle);
+        }
+
+        let width = max_x
+            .checked_sub(&min_x)
+            .ok_or(ParseWktError::Area(Error::Overflow))?;
+        let height = max_y
+            .checked_sub(&min_y)
+            .ok_or(ParseWktError::Area(Error::Overflow))?;
+        Area::new((min_x, min_y), (width, height)).map_err(ParseWktError::Area)
+    }
+}
+
+/// Writes the regions of the given entries as a WKT `GEOMETRYCOLLECTION` of `POLYGON`s, i.e.
+/// the results of a [`.query()`].
+///
+/// [`.query()`]: ../struct.Quadtree.html#method.query
+pub fn geometry_collection<'a, U, V, I>(entries: I) -> String
+where
+    U: PrimInt + Default + fmt::Display + 'a,
+    V: 'a,
+    I: IntoIterator<Item = &'a Entry<U, V>>,
+{
+    let polygons: Vec<String> = entries
+        .into_iter()
+        .map(|entry| entry.area().to_string())
+        .collect();
+    if polygons.is_empty() {
+        "GEOMETRYCOLLECTION EMPTY".to_string()
+    } else {
+        format!("GEOMETRYCOLLECTION ({})", polygons.join(", "))
+    }
+}
+
+impl<U, V> Quadtree<U, V>
+where
+    U: PrimInt + Default + fmt::Display,
+{
+    /// Writes the region of every entry, in handle order, as a WKT `GEOMETRYCOLLECTION`.
+    /// ```
+    /// use quadtree_rs::Quadtree;
+    ///
+    /// let mut qt = Quadtree::<u32, ()>::new(4);
+    /// assert_eq!(qt.to_wkt(), "GEOMETRYCOLLECTION EMPTY");
+    ///
+    /// qt.insert(((0, 0), (2, 1)), ());
+    /// qt.insert((5, 5), ());
+    /// assert_eq!(
+    ///     qt.to_wkt(),
+    ///     "GEOMETRYCOLLECTION (POLYGON ((0 0, 2 0, 2 1, 0 1, 0 0)), \
+    ///      POLYGON ((5 5, 6 5, 6 6, 5 6, 5 5)))"
+    /// );
+    /// ```
+    pub fn to_wkt(&self) -> String {
+        geometry_collection(self.entries_by_handle())
+    }
+}
+
+// Strips the case-insensitive @keyword from the start of @s.
+fn keyword<'a>(s: &'a str, keyword: &str) -> Result<&'a str, ParseWktError> {
+    let s = s.trim();
+    match s.get(..keyword.len()) {
+        Some(head) if head.eq_ignore_ascii_case(keyword) => Ok(&s[keyword.len()..]),
+        _ => Err(ParseWktError::Syntax),
+    }
+}
+
+// The text between an opening parenthesis at the start of @s and a closing one at its end.
+fn parenthesized(s: &str) -> Result<&str, ParseWktError> {
+    s.trim()
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or(ParseWktError::Syntax)
+}
+
+// Parses an "x y" position.
+fn parse_position<U>(s: &str) -> Result<Point<U>, ParseWktError>
+where
+    U: FromStr,
+{
+    let mut coordinates = s.split_whitespace().map(str::parse::<U>);
+    match (coordinates.next(), coordinates.next(), coordinates.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point { x, y }),
+        _ => Err(ParseWktError::Syntax),
+    }
+}
+
+fn min_max<U: PrimInt>(mut values: impl Iterator<Item = U>) -> (U, U) {
+    let first = values.next().unwrap_or_else(U::zero);
+    values.fold((first, first), |(lo, hi), v| (lo.min(v), hi.max(v)))
+}
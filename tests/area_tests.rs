@@ -23,7 +23,7 @@ mod area_tests {
 
         #[test]
         fn builder() {
-            let a: Area<i8> = ((0, 0), (2, 2)).into();
+            let a: Area<i8> = Area::new((0, 0), (2, 2)).unwrap();
             debug_assert_eq!(a.width(), 2);
         }
     }
@@ -39,6 +39,58 @@ mod area_tests {
         }
     }
 
+    #[test]
+    fn try_from() {
+        use quadtree_rs::Error;
+        use std::convert::TryFrom;
+
+        debug_assert_eq!(
+            Area::<i8>::try_from(((0, 0), (2, 2))),
+            Area::new((0, 0), (2, 2))
+        );
+        for dims in [(-1, 4), (1, -4), (0, 4), (1, 0)].iter() {
+            debug_assert_eq!(
+                Area::<i8>::try_from(((0, 0), *dims)),
+                Err(Error::InvalidDimensions)
+            );
+        }
+        // The bottom-right corner doesn't fit in an i8.
+        debug_assert_eq!(
+            Area::<i8>::try_from(((100, 0), (28, 1))),
+            Err(Error::Overflow)
+        );
+        debug_assert_eq!(
+            Area::<i8>::try_from(((0, 100), (1, 28))),
+            Err(Error::Overflow)
+        );
+        debug_assert!(Area::<i8>::try_from(((100, 100), (27, 27))).is_ok());
+    }
+
+    // An invalid region neither intersects nor is contained by anything.
+    #[test]
+    fn invalid_regions() {
+        let a: Area<i8> = Area::new((0, 0), (4, 4)).unwrap();
+        debug_assert!(!a.intersects(((1, 1), (0, 1))));
+        debug_assert!(!a.contains(((1, 1), (1, -1))));
+    }
+
+    // Regions built unchecked saturate rather than overflow.
+    #[test]
+    fn saturating_edges() {
+        let a: Area<u8> = (255, 255).into();
+        debug_assert_eq!(a.right_edge(), 255);
+        debug_assert_eq!(a.bottom_edge(), 255);
+        debug_assert!(!a.contains_pt((255, 255)));
+
+        let b: Area<u8> = AreaBuilder::default()
+            .anchor((250, 250))
+            .dimensions((10, 10))
+            .build()
+            .unwrap();
+        debug_assert_eq!(b.right_edge(), 255);
+        debug_assert!(b.contains_pt((254, 254)));
+    }
+
     #[test]
     fn point_in_all_quadrants() {
         for p in [(1, 1), (-1, 1), (1, -1), (-1, -1)].iter() {
@@ -48,7 +100,7 @@ mod area_tests {
 
     #[test]
     fn properties() {
-        let a: Area<u8> = ((3, 4), (5, 7)).into();
+        let a: Area<u8> = Area::new((3, 4), (5, 7)).unwrap();
 
         debug_assert_eq!(a.anchor(), (3, 4).into());
         debug_assert_eq!(a.width(), 5);
@@ -76,7 +128,7 @@ mod area_tests {
         // 4 +--+--+--+--+
 
         fn test_area() -> Area<u8> {
-            ((1, 1), (2, 2)).into()
+            Area::new((1, 1), (2, 2)).unwrap()
         }
 
         #[test]
@@ -177,7 +229,7 @@ mod area_tests {
         // 2 +--+--+--+--+
 
         fn test_area() -> Area<i8> {
-            ((-1, -1), (2, 2)).into()
+            Area::new((-1, -1), (2, 2)).unwrap()
         }
 
         #[test]
@@ -290,7 +342,7 @@ mod area_tests {
         // 6 +--+--+--+--+--+--+
 
         fn test_area() -> Area<u8> {
-            ((2, 2), (2, 2)).into()
+            Area::new((2, 2), (2, 2)).unwrap()
         }
 
         // All the 1x1s obviously contains.
@@ -345,8 +397,8 @@ mod area_tests {
 
         #[test]
         fn regression_test() {
-            let a: Area<u8> = ((3, 3), (2, 2)).into();
-            let b: Area<u8> = ((0, 0), (6, 6)).into();
+            let a: Area<u8> = Area::new((3, 3), (2, 2)).unwrap();
+            let b: Area<u8> = Area::new((0, 0), (6, 6)).unwrap();
 
             debug_assert!(b.intersects(a));
             debug_assert!(a.intersects(b));
@@ -373,7 +425,7 @@ mod area_tests {
         // 3 +--+--+--+--+--+--+
 
         fn test_area() -> Area<i8> {
-            ((-1, -1), (2, 2)).into()
+            Area::new((-1, -1), (2, 2)).unwrap()
         }
 
         #[test]
@@ -0,0 +1,156 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .to_bytes(), .to_bytes_with_tree(), and .from_bytes().
+mod binary_tests {
+    use quadtree_rs::{
+        binary::DecodeError,
+        Quadtree,
+    };
+
+    // With u8 coordinates, the header is 32 bytes long.
+    const HEADER_LEN: usize = 32;
+
+    fn mk_quadtree() -> Quadtree<i16, String> {
+        let mut qt = Quadtree::<i16, String>::new_with_anchor((-8, -8).into(), 4);
+        qt.insert(((-8, -8), (16, 16)), "everything".to_string());
+        let doomed = qt.insert((-3, 2), "doomed".to_string()).unwrap();
+        let moved = qt.insert(((1, 1), (2, 3)), "moved".to_string()).unwrap();
+        qt.insert((7, 7), "corner".to_string());
+        // Leave some history behind: a deleted handle, and a moved one.
+        qt.delete_by_handle(doomed);
+        qt.move_to(moved, ((-6, 0), (2, 3))).unwrap();
+        qt
+    }
+
+    #[test]
+    fn round_trip() {
+        let qt = mk_quadtree();
+        for bytes in [qt.to_bytes().unwrap(), qt.to_bytes_with_tree().unwrap()] {
+            let copy = Quadtree::<i16, String>::from_bytes(&bytes).unwrap();
+            debug_assert_eq!(copy.anchor(), qt.anchor());
+            debug_assert_eq!(copy.depth(), qt.depth());
+            debug_assert_eq!(copy.len(), qt.len());
+            for entry in qt.iter() {
+                debug_assert_eq!(copy.get(entry.handle()), Some(entry));
+            }
+            for region in [((-8, -8), (1, 1)), ((-5, 1), (1, 1)), ((0, 0), (8, 8))] {
+                let mut expected: Vec<u64> = qt.query(region).map(|e| e.handle()).collect();
+                let mut actual: Vec<u64> = copy.query(region).map(|e| e.handle()).collect();
+                expected.sort_unstable();
+                actual.sort_unstable();
+                debug_assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn handle_counter_is_kept() {
+        let mut qt = mk_quadtree();
+        let mut copy = Quadtree::<i16, String>::from_bytes(&qt.to_bytes().unwrap()).unwrap();
+        debug_assert_eq!(
+            copy.insert((0, 0), "next".to_string()),
+            qt.insert((0, 0), "next".to_string())
+        );
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        debug_assert_eq!(
+            mk_quadtree().to_bytes().unwrap(),
+            mk_quadtree().to_bytes().unwrap()
+        );
+        let mut qt = Quadtree::<u8, u8>::new(3);
+        qt.insert((1, 2), 3);
+        debug_assert_eq!(qt.to_bytes().unwrap().len(), HEADER_LEN + 17);
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = mk_quadtree().to_bytes_with_tree().unwrap();
+        for len in 0..bytes.len() {
+            debug_assert_eq!(
+                Quadtree::<i16, String>::from_bytes(&bytes[..len]),
+                Err(DecodeError::Truncated)
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_header() {
+        let mut qt = Quadtree::<u8, u8>::new(3);
+        qt.insert((1, 2), 3);
+        let bytes = qt.to_bytes().unwrap();
+        let with = |i: usize, b: u8| {
+            let mut bytes = bytes.clone();
+            bytes[i] = b;
+            Quadtree::<u8, u8>::from_bytes(&bytes)
+        };
+
+        debug_assert_eq!(with(0, b'X'), Err(DecodeError::BadMagic));
+        debug_assert_eq!(with(4, 2), Err(DecodeError::UnsupportedVersion(2)));
+        debug_assert_eq!(with(6, 2), Err(DecodeError::UnsupportedFlags(2)));
+        debug_assert_eq!(with(9, 1), Err(DecodeError::CoordinateMismatch));
+        // A depth of 9 is too wide for u8.
+        debug_assert_eq!(with(10, 9), Err(DecodeError::InvalidRoot));
+        // A handle counter of 0 can't have handed out handle 0.
+        debug_assert_eq!(with(16, 0), Err(DecodeError::InvalidEntry(0)));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::from_bytes(&trailing),
+            Err(DecodeError::TrailingBytes)
+        );
+    }
+
+    #[test]
+    fn invalid_entries() {
+        let mut qt = Quadtree::<u8, bool>::new(3);
+        qt.insert((1, 2), true);
+        qt.insert((3, 4), false);
+        let bytes = qt.to_bytes().unwrap();
+        let with = |i: usize, b: u8| {
+            let mut bytes = bytes.clone();
+            bytes[HEADER_LEN + i] = b;
+            Quadtree::<u8, bool>::from_bytes(&bytes)
+        };
+
+        // The first entry's anchor, width, and value.
+        debug_assert_eq!(with(8, 8), Err(DecodeError::InvalidEntry(0)));
+        debug_assert_eq!(with(10, 0), Err(DecodeError::InvalidEntry(0)));
+        debug_assert_eq!(with(16, 2), Err(DecodeError::InvalidValue(0)));
+        // The second entry's handle.
+        debug_assert_eq!(with(17, 0), Err(DecodeError::DuplicateHandle(0)));
+    }
+
+    #[test]
+    fn tree_mismatch() {
+        let mut qt = Quadtree::<u8, u8>::new(1);
+        qt.insert((1, 1), 5);
+        let mut bytes = qt.to_bytes_with_tree().unwrap();
+
+        // The tree is a root with no handles, and one subquadrant holding handle 0. Move the handle
+        // to the root.
+        let tree = HEADER_LEN + 17;
+        debug_assert_eq!(bytes.len(), tree + 5 + 4 * 5 + 8);
+        debug_assert!(Quadtree::<u8, u8>::from_bytes(&bytes).is_ok());
+        bytes.truncate(tree);
+        bytes.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::from_bytes(&bytes),
+            Err(DecodeError::TreeMismatch)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing ConcurrentQuadtree.
+mod concurrent_tests {
+    use quadtree_rs::{
+        area::Area,
+        ConcurrentQuadtree,
+        Error,
+        Quadtree,
+    };
+    use std::{
+        collections::HashSet,
+        thread,
+    };
+
+    fn sorted(mut handles: Vec<u64>) -> Vec<u64> {
+        handles.sort_unstable();
+        handles
+    }
+
+    #[test]
+    fn matches_quadtree() {
+        let cqt = ConcurrentQuadtree::<u8, u32>::new(4);
+        let mut qt = Quadtree::<u8, u32>::new(4);
+
+        let mut seed: u32 = 7;
+        let mut rand = |m: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % m
+        };
+        let mut area = || {
+            let (x, y) = (rand(16) as u8, rand(16) as u8);
+            Area::new(
+                (x, y),
+                (1 + rand(16 - x as u32) as u8, 1 + rand(16 - y as u32) as u8),
+            )
+            .unwrap()
+        };
+
+        for i in 0..300 {
+            let region = area();
+            debug_assert_eq!(cqt.insert(region, i), qt.insert(region, i));
+            if i % 3 == 0 {
+                let handle = u64::from(i) / 2;
+                debug_assert_eq!(cqt.delete_by_handle(handle), qt.delete_by_handle(handle));
+            }
+        }
+        debug_assert_eq!(cqt.len(), qt.len());
+
+        for _ in 0..100 {
+            let query = area();
+            debug_assert_eq!(
+                sorted(cqt.query(query).iter().map(|e| e.handle()).collect()),
+                sorted(qt.query(query).map(|e| e.handle()).collect())
+            );
+            debug_assert_eq!(
+                sorted(cqt.query_strict(query).iter().map(|e| e.handle()).collect()),
+                sorted(qt.query_strict(query).map(|e| e.handle()).collect())
+            );
+        }
+    }
+
+    #[test]
+    fn spanning_entries() {
+        let cqt = ConcurrentQuadtree::<i32, char>::new_with_anchor((-4, -4).into(), 3);
+        let everything = cqt.insert(((-4, -4), (8, 8)), 'e').unwrap();
+        let straddler = cqt.insert(((-1, -1), (2, 2)), 's').unwrap();
+        let corner = cqt.insert((3, 3), 'c').unwrap();
+
+        debug_assert_eq!(
+            sorted(cqt.query((0, 0)).iter().map(|e| e.handle()).collect()),
+            vec![everything, straddler]
+        );
+        debug_assert_eq!(
+            sorted(cqt.query((3, 3)).iter().map(|e| e.handle()).collect()),
+            vec![everything, corner]
+        );
+        debug_assert_eq!(cqt.get(straddler).unwrap().value_ref(), &'s');
+        debug_assert_eq!(cqt.insert((4, 4), 'x'), None);
+
+        debug_assert_eq!(cqt.delete_by_handle(straddler).unwrap().value_ref(), &'s');
+        debug_assert!(cqt.delete_by_handle(straddler).is_none());
+        debug_assert!(cqt.get(straddler).is_none());
+        debug_assert_eq!(cqt.len(), 2);
+    }
+
+    #[test]
+    fn depth_zero() {
+        let cqt = ConcurrentQuadtree::<u8, ()>::new(0);
+        let handle = cqt.insert((0, 0), ()).unwrap();
+        debug_assert_eq!(cqt.insert((1, 0), ()), None);
+        debug_assert_eq!(cqt.query((0, 0)).len(), 1);
+        debug_assert!(cqt.delete_by_handle(handle).is_some());
+        debug_assert!(cqt.is_empty());
+    }
+
+    #[test]
+    fn try_new() {
+        debug_assert_eq!(
+            ConcurrentQuadtree::<u8, u8>::try_new(7).unwrap().width(),
+            128
+        );
+        debug_assert_eq!(
+            ConcurrentQuadtree::<u8, u8>::try_new(8).err(),
+            Some(Error::Overflow)
+        );
+        debug_assert_eq!(
+            ConcurrentQuadtree::<u8, u8>::try_new_with_anchor((200, 0).into(), 7).err(),
+            Some(Error::Overflow)
+        );
+    }
+
+    #[test]
+    fn conversions_keep_handles() {
+        let mut qt = Quadtree::<u16, &str>::new(5);
+        let a = qt.insert(((0, 0), (20, 20)), "a").unwrap();
+        let doomed = qt.insert((3, 3), "doomed").unwrap();
+        let b = qt.insert((30, 1), "b").unwrap();
+        qt.delete_by_handle(doomed);
+
+        let cqt = ConcurrentQuadtree::from(qt);
+        debug_assert_eq!(cqt.get(a).unwrap().value_ref(), &"a");
+        debug_assert_eq!(cqt.get(b).unwrap().value_ref(), &"b");
+        // Handles aren't reused.
+        let c = cqt.insert((1, 30), "c").unwrap();
+        debug_assert!(c > b);
+
+        let mut qt = Quadtree::from(cqt);
+        debug_assert_eq!(qt.len(), 3);
+        debug_assert_eq!(qt.get(c).unwrap().value_ref(), &"c");
+        debug_assert_eq!(
+            sorted(qt.query((2, 2)).map(|e| e.handle()).collect()),
+            vec![a]
+        );
+        debug_assert!(qt.insert((5, 5), "d").unwrap() > c);
+    }
+
+    #[test]
+    fn concurrent_writers_and_readers() {
+        let cqt = ConcurrentQuadtree::<u32, u32>::new(8);
+
+        let handles: Vec<Vec<u64>> = thread::scope(|s| {
+            let writers: Vec<_> = (0..4)
+                .map(|t| {
+                    let cqt = &cqt;
+                    s.spawn(move || {
+                        let mut mine = vec![];
+                        for i in 0..200 {
+                            // Each writer mostly stays in its own quadrant, with a few entries
+                            // spanning the middle of the tree.
+                            let (x, y) = (128 * (t % 2) + i % 100, 128 * (t / 2) + i / 2);
+                            let region = if i % 10 == 0 {
+                                ((120, 120), (16, 16))
+                            } else {
+                                ((x, y), (1, 1))
+                            };
+                            let handle = cqt.insert(region, t).unwrap();
+                            if i % 4 == 0 {
+                                debug_assert!(cqt.delete_by_handle(handle).is_some());
+                            } else {
+                                mine.push(handle);
+                            }
+                        }
+                        mine
+                    })
+                })
+                .collect();
+            for _ in 0..4 {
+                let cqt = &cqt;
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        let mut seen = HashSet::new();
+                        cqt.query_with(((0, 0), (256, 256)), |e| {
+                            debug_assert!(seen.insert(e.handle()));
+                        });
+                    }
+                });
+            }
+            writers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+
+        let all: Vec<u64> = handles.into_iter().flatten().collect();
+        debug_assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+        debug_assert_eq!(cqt.len(), all.len());
+        debug_assert_eq!(
+            sorted(
+                cqt.query(((0, 0), (256, 256)))
+                    .iter()
+                    .map(|e| e.handle())
+                    .collect()
+            ),
+            sorted(all)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .read_csv(), .write_csv(), and csv::write().
+mod csv_tests {
+    use quadtree_rs::{
+        csv::{
+            self,
+            CsvError,
+            Format,
+            FormatBuilder,
+        },
+        Error,
+        Quadtree,
+    };
+    use std::io::{
+        self,
+        BufRead,
+        Read,
+    };
+
+    fn read_strings(
+        qt: &mut Quadtree<i32, String>,
+        input: &str,
+        format: &Format,
+    ) -> Vec<Result<u64, CsvError<()>>> {
+        qt.read_csv(input.as_bytes(), format, |s| Ok(s.to_string()))
+            .collect()
+    }
+
+    fn write_strings(qt: &Quadtree<i32, String>, format: &Format) -> String {
+        let mut output = Vec::new();
+        qt.write_csv(&mut output, format, |v| v.clone()).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn round_trip_with_quoting() {
+        let mut qt = Quadtree::<i32, String>::new_with_anchor((-8, -8).into(), 4);
+        for value in &["plain", "a,b", "say \"hi\"", "two\nlines", "", " padded "] {
+            qt.insert(((-3, 2), (2, 1)), value.to_string());
+        }
+        let format = Format::default();
+        let text = write_strings(&qt, &format);
+
+        let mut copy = Quadtree::<i32, String>::new_with_anchor((-8, -8).into(), 4);
+        let results = read_strings(&mut copy, &text, &format);
+        debug_assert_eq!(results.len(), 6);
+        debug_assert!(results.iter().all(Result::is_ok));
+        debug_assert_eq!(write_strings(&copy, &format), text);
+    }
+
+    #[test]
+    fn custom_columns_header_and_delimiter() {
+        let format = FormatBuilder::default()
+            .value(0)
+            .height(1)
+            .width(2)
+            .y(3)
+            .x(5)
+            .header(true)
+            .delimiter(';')
+            .build()
+            .unwrap();
+
+        let input = "name;h;w;y;ignored;x\r\n\r\n\"a;b\";2;3;4;?;1\r\n";
+        let mut qt = Quadtree::<i32, String>::new(4);
+        let handle = read_strings(&mut qt, input, &format)[0]
+            .as_ref()
+            .copied()
+            .unwrap();
+
+        let entry = qt.get(handle).unwrap();
+        debug_assert_eq!(entry.anchor(), (1, 4).into());
+        debug_assert_eq!((entry.width(), entry.height()), (3, 2));
+        debug_assert_eq!(entry.value_ref(), "a;b");
+
+        // Unused columns are left empty.
+        debug_assert_eq!(
+            write_strings(&qt, &format),
+            "value;height;width;y;;x\n\"a;b\";2;3;4;;1\n"
+        );
+    }
+
+    #[test]
+    fn invalid_formats() {
+        debug_assert!(FormatBuilder::default().value(0).build().is_err());
+        debug_assert!(FormatBuilder::default().delimiter('"').build().is_err());
+        debug_assert!(FormatBuilder::default().delimiter('\n').build().is_err());
+        debug_assert!(FormatBuilder::default().delimiter('\t').build().is_ok());
+    }
+
+    #[test]
+    fn bad_rows_are_reported_and_skipped() {
+        let input = "\
+1,1,1,1,ok
+1,1,1
+1,one,1,1,bad number
+1,1,0,1,empty
+15,15,2,2,out of bounds
+2,2,1,1,\"multi
+line\"
+3,3,1,1,bad value
+4,4,1,1,ok again
+";
+        let mut qt = Quadtree::<u8, String>::new(4);
+        let results: Vec<_> = qt
+            .read_csv(input.as_bytes(), &Format::default(), |s| {
+                if s == "bad value" {
+                    Err("no")
+                } else {
+                    Ok(s.to_string())
+                }
+            })
+            .collect();
+
+        debug_assert_eq!(results.len(), 8);
+        debug_assert!(results[0].is_ok());
+        debug_assert!(matches!(results[1], Err(CsvError::MissingColumn(2, 3))));
+        debug_assert!(matches!(results[2], Err(CsvError::InvalidNumber(3, 1))));
+        debug_assert!(matches!(
+            results[3],
+            Err(CsvError::Insert(4, Error::InvalidDimensions))
+        ));
+        debug_assert!(matches!(
+            results[4],
+            Err(CsvError::Insert(5, Error::OutOfBounds))
+        ));
+        debug_assert!(results[5].is_ok());
+        debug_assert!(matches!(results[6], Err(CsvError::InvalidValue(8, "no"))));
+        debug_assert!(results[7].is_ok());
+        debug_assert_eq!(qt.len(), 3);
+
+        let multi = *results[5].as_ref().unwrap();
+        debug_assert_eq!(qt.get(multi).unwrap().value_ref(), "multi\nline");
+    }
+
+    #[test]
+    fn unterminated_quote_ends_the_input() {
+        let mut qt = Quadtree::<i32, String>::new(4);
+        let results = read_strings(
+            &mut qt,
+            "1,1,1,1,a\n2,2,1,1,\"b\n3,3,1,1,c\n",
+            &Format::default(),
+        );
+        debug_assert_eq!(results.len(), 2);
+        debug_assert!(results[0].is_ok());
+        debug_assert!(matches!(results[1], Err(CsvError::UnterminatedQuote(2))));
+    }
+
+    // A reader which fails after its first line.
+    struct Flaky(bool);
+
+    impl Read for Flaky {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            unreachable!()
+        }
+    }
+
+    impl BufRead for Flaky {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            if self.0 {
+                Err(io::Error::other("flaky"))
+            } else {
+                Ok(b"1,1,1,1,a\n")
+            }
+        }
+
+        fn consume(&mut self, _: usize) {
+            self.0 = true;
+        }
+    }
+
+    #[test]
+    fn io_errors_stop_reading() {
+        let mut qt = Quadtree::<i32, String>::new(4);
+        let results: Vec<_> = qt
+            .read_csv(Flaky(false), &Format::default(), |s| {
+                Ok::<_, ()>(s.to_string())
+            })
+            .collect();
+        debug_assert_eq!(results.len(), 2);
+        debug_assert!(results[0].is_ok());
+        debug_assert!(matches!(results[1], Err(CsvError::Io(_))));
+    }
+
+    #[test]
+    fn rows_are_read_lazily() {
+        let mut qt = Quadtree::<i32, String>::new(4);
+        let mut rows = qt.read_csv(
+            "1,1,1,1,a\n2,2,1,1,b\n".as_bytes(),
+            &Format::default(),
+            |s| Ok::<_, ()>(s.to_string()),
+        );
+        debug_assert!(rows.next().unwrap().is_ok());
+        drop(rows);
+        debug_assert_eq!(qt.len(), 1);
+    }
+
+    #[test]
+    fn write_a_query() {
+        let mut qt = Quadtree::<i32, String>::new(4);
+        qt.insert(((0, 0), (2, 2)), "near".to_string());
+        qt.insert(((10, 10), (2, 2)), "far".to_string());
+
+        let format = FormatBuilder::default().header(true).build().unwrap();
+        let mut output = Vec::new();
+        csv::write(qt.query(((1, 1), (1, 1))), &mut output, &format, |v| {
+            v.clone()
+        })
+        .unwrap();
+        debug_assert_eq!(output, b"x,y,width,height,value\n0,0,2,2,near\n");
+    }
+}
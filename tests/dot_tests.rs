@@ -0,0 +1,79 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .to_dot().
+mod dot_tests {
+    use quadtree_rs::Quadtree;
+
+    #[test]
+    fn empty_tree_is_just_the_root() {
+        let qt = Quadtree::<u8, ()>::new(2);
+        debug_assert_eq!(
+            qt.to_dot(),
+            r#"digraph quadtree {
+  label="0 entries, 0 handles";
+  node [shape=box, style=filled, fillcolor=white];
+  n0 [label="(0x0)->4x4\ndepth 2\n0 handles"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn subdivision_and_replicated_handles() {
+        let mut qt = Quadtree::<i8, ()>::new_with_anchor((-1, -1).into(), 1);
+        // Held by the root.
+        qt.insert(((-1, -1), (2, 2)), ());
+        // Replicated into the two southern subquadrants.
+        qt.insert(((-1, 0), (2, 1)), ());
+
+        debug_assert_eq!(
+            qt.to_dot(),
+            r#"digraph quadtree {
+  label="2 entries, 3 handles";
+  node [shape=box, style=filled, fillcolor=white];
+  n0 [label="(-1x-1)->2x2\ndepth 1\n1 handles", fillcolor=lightblue];
+  n1 [label="(0x-1)->1x1\ndepth 0\n0 handles"];
+  n0 -> n1 [label="ne"];
+  n2 [label="(-1x-1)->1x1\ndepth 0\n0 handles"];
+  n0 -> n2 [label="nw"];
+  n3 [label="(0x0)->1x1\ndepth 0\n1 handles", fillcolor=lightblue];
+  n0 -> n3 [label="se"];
+  n4 [label="(-1x0)->1x1\ndepth 0\n1 handles", fillcolor=lightblue];
+  n0 -> n4 [label="sw"];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn ids_are_pre_order() {
+        let mut qt = Quadtree::<u8, ()>::new(2);
+        // Subdivides the root, and then its southeast subquadrant.
+        qt.insert((3, 3), ());
+        let dot = qt.to_dot();
+
+        debug_assert_eq!(dot.matches(" -> ").count(), 8);
+        for edge in &[
+            "n0 -> n1 ",
+            "n0 -> n2 ",
+            "n0 -> n3 ",
+            "n3 -> n4 ",
+            "n3 -> n7 ",
+            "n0 -> n8 ",
+        ] {
+            debug_assert!(dot.contains(edge), "{}", edge);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .freeze() and FrozenQuadtree.
+mod frozen_tests {
+    use quadtree_rs::{
+        binary::DecodeError,
+        frozen::FrozenQuadtree,
+        Quadtree,
+    };
+
+    fn mk_quadtree() -> Quadtree<i16, String> {
+        let mut qt = Quadtree::<i16, String>::new_with_anchor((-8, -8).into(), 4);
+        qt.insert(((-8, -8), (16, 16)), "everything".to_string());
+        let doomed = qt.insert((-3, 2), "doomed".to_string()).unwrap();
+        qt.insert(((1, 1), (2, 3)), "small".to_string());
+        qt.insert(((-5, -5), (9, 3)), "wide".to_string());
+        qt.insert((7, 7), "corner".to_string());
+        qt.delete_by_handle(doomed);
+        qt
+    }
+
+    fn sorted(mut handles: Vec<u64>) -> Vec<u64> {
+        handles.sort_unstable();
+        handles
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let qt = mk_quadtree();
+        let bytes = qt.freeze();
+        let frozen = FrozenQuadtree::<i16, String>::open(&bytes).unwrap();
+
+        debug_assert_eq!(frozen.anchor(), qt.anchor());
+        debug_assert_eq!(frozen.depth(), qt.depth());
+        debug_assert_eq!(frozen.width(), qt.width());
+        debug_assert_eq!(frozen.len(), qt.len());
+
+        let mut handles = vec![];
+        for entry in frozen.iter() {
+            let original = qt.get(entry.handle()).unwrap();
+            debug_assert_eq!(entry.area(), original.area());
+            debug_assert_eq!(entry.value().as_ref(), Some(original.value_ref()));
+            debug_assert_eq!(entry.value_bytes(), original.value_ref().as_bytes());
+            handles.push(entry.handle());
+        }
+        // In handle order.
+        debug_assert_eq!(handles, sorted(qt.iter().map(|e| e.handle()).collect()));
+
+        for handle in 0..8 {
+            debug_assert_eq!(
+                frozen.get(handle).map(|e| e.area()),
+                qt.get(handle).map(|e| e.area())
+            );
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let qt = Quadtree::<u8, ()>::new(3);
+        let bytes = qt.freeze();
+        let frozen = FrozenQuadtree::<u8, ()>::open(&bytes).unwrap();
+        debug_assert!(frozen.is_empty());
+        debug_assert_eq!(frozen.query(((0, 0), (8, 8))).count(), 0);
+        debug_assert!(frozen.get(0).is_none());
+    }
+
+    // Compare queries against the original tree over a deterministic scattering of regions.
+    #[test]
+    fn queries_brute_force() {
+        let mut qt = Quadtree::<u32, u32>::new(5);
+        let mut seed: u32 = 41;
+        let mut next = |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+        for i in 0..100 {
+            let (x, y) = (next(30), next(30));
+            let (w, h) = (1 + next(32 - x), 1 + next(32 - y));
+            assert!(qt.insert(((x, y), (w, h)), i).is_some());
+        }
+        let bytes = qt.freeze();
+        let frozen = FrozenQuadtree::<u32, u32>::open(&bytes).unwrap();
+
+        for _ in 0..200 {
+            let (x, y) = (next(32), next(32));
+            let (w, h) = (1 + next(32 - x), 1 + next(32 - y));
+            let area = ((x, y), (w, h));
+            debug_assert_eq!(
+                sorted(frozen.query(area).map(|e| e.handle()).collect()),
+                sorted(qt.query(area).map(|e| e.handle()).collect())
+            );
+            debug_assert_eq!(
+                sorted(frozen.query_strict(area).map(|e| e.handle()).collect()),
+                sorted(qt.query_strict(area).map(|e| e.handle()).collect())
+            );
+        }
+        for x in 0..32 {
+            for y in 0..32 {
+                debug_assert_eq!(
+                    sorted(frozen.query_pt((x, y)).map(|e| e.handle()).collect()),
+                    sorted(qt.query((x, y)).map(|e| e.handle()).collect())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn queries_outside_the_tree() {
+        let qt = mk_quadtree();
+        let bytes = qt.freeze();
+        let frozen = FrozenQuadtree::<i16, String>::open(&bytes).unwrap();
+        debug_assert_eq!(frozen.query(((20, 20), (2, 2))).count(), 0);
+        debug_assert_eq!(frozen.query_pt((-9, 0)).count(), 0);
+        // An invalid region matches nothing.
+        debug_assert_eq!(frozen.query(((0, 0), (0, 1))).count(), 0);
+    }
+
+    #[test]
+    fn open_checks_the_header_and_sizes() {
+        let bytes = mk_quadtree().freeze();
+        type Frozen<'a> = FrozenQuadtree<'a, i16, String>;
+
+        debug_assert_eq!(Frozen::open(&[]).unwrap_err(), DecodeError::Truncated);
+        debug_assert_eq!(
+            Frozen::open(&bytes[..bytes.len() - 1]).unwrap_err(),
+            DecodeError::Truncated
+        );
+        let mut long = bytes.clone();
+        long.push(0);
+        debug_assert_eq!(Frozen::open(&long).unwrap_err(), DecodeError::TrailingBytes);
+
+        let mut bad = bytes.clone();
+        bad[4] = 2;
+        debug_assert_eq!(
+            Frozen::open(&bad).unwrap_err(),
+            DecodeError::UnsupportedVersion(2)
+        );
+        let mut bad = bytes.clone();
+        bad[6] = 1;
+        debug_assert_eq!(
+            Frozen::open(&bad).unwrap_err(),
+            DecodeError::UnsupportedFlags(1)
+        );
+        debug_assert_eq!(
+            FrozenQuadtree::<u16, String>::open(&bytes).unwrap_err(),
+            DecodeError::CoordinateMismatch
+        );
+        // A depth of 16 doesn't fit in an i16.
+        let mut bad = bytes.clone();
+        bad[12] = 16;
+        debug_assert_eq!(Frozen::open(&bad).unwrap_err(), DecodeError::InvalidRoot);
+    }
+
+    // Corrupting any single byte past the header must not cause a panic or an endless query.
+    #[test]
+    fn corrupt_records_are_skipped() {
+        let bytes = mk_quadtree().freeze();
+        // The header of an i16 tree is 16 + 2 * 2 + 4 * 8 bytes long.
+        for i in 52..bytes.len() {
+            for &flip in &[0x01, 0x80, 0xff] {
+                let mut bad = bytes.clone();
+                bad[i] ^= flip;
+                let frozen = FrozenQuadtree::<i16, String>::open(&bad).unwrap();
+                let found = frozen.query(((-8, -8), (16, 16))).count();
+                debug_assert!(found <= bad.len());
+                let _ = frozen.iter().filter_map(|e| e.value()).count();
+                let _ = frozen.get(2);
+            }
+        }
+    }
+
+    #[test]
+    fn huge_child_offsets_are_skipped() {
+        let bytes = mk_quadtree().freeze();
+        let everything = FrozenQuadtree::<i16, String>::open(&bytes)
+            .unwrap()
+            .query(((-8, -8), (16, 16)))
+            .count();
+        // The root's subquadrant offset follows its reference range, after the header.
+        let mut bad = bytes.clone();
+        bad[52 + 16..52 + 24].copy_from_slice(&u64::MAX.to_le_bytes());
+        let frozen = FrozenQuadtree::<i16, String>::open(&bad).unwrap();
+        debug_assert!(frozen.query(((-8, -8), (16, 16))).count() <= everything);
+        debug_assert_eq!(frozen.iter().count(), frozen.len());
+    }
+}
@@ -0,0 +1,156 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "geojson")]
+
+// For testing .insert_geojson(), .to_geojson(), and geojson::feature_collection().
+mod geojson_tests {
+    use quadtree_rs::{
+        area::Area,
+        geojson::{
+            self,
+            GeoJsonError,
+        },
+        Error,
+        Quadtree,
+    };
+    use serde_json::{
+        json,
+        Value,
+    };
+
+    fn collection(geometries: Vec<Value>) -> String {
+        let features: Vec<Value> = geometries
+            .into_iter()
+            .enumerate()
+            .map(|(i, geometry)| {
+                json!({"type": "Feature", "geometry": geometry, "properties": {"n": i}})
+            })
+            .collect();
+        json!({"type": "FeatureCollection", "features": features}).to_string()
+    }
+
+    #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
+    struct Props {
+        n: usize,
+    }
+
+    #[test]
+    fn bounding_boxes() {
+        let mut qt = Quadtree::<i32, Props>::new_with_anchor((-16, -16).into(), 5);
+        let handles = qt
+            .insert_geojson(&collection(vec![
+                json!({"type": "Point", "coordinates": [3, 4]}),
+                json!({"type": "Polygon", "coordinates": [[[0, 0], [5, 0], [5, 2.5], [0, 0]]]}),
+                json!({"type": "MultiPoint", "coordinates": [[-3.5, 1], [2, -7]]}),
+                json!({
+                    "type": "GeometryCollection",
+                    "geometries": [
+                        {"type": "Point", "coordinates": [1, 1]},
+                        {"type": "LineString", "coordinates": [[2, 2], [6, 2]]},
+                    ],
+                }),
+            ]))
+            .unwrap();
+
+        let areas: Vec<Area<i32>> = handles.iter().map(|h| qt.get(*h).unwrap().area()).collect();
+        debug_assert_eq!(
+            areas,
+            vec![
+                Area::new((3, 4), (1, 1)).unwrap(),
+                Area::new((0, 0), (5, 3)).unwrap(),
+                Area::new((-4, -7), (6, 8)).unwrap(),
+                Area::new((1, 1), (5, 1)).unwrap(),
+            ]
+        );
+        for (i, handle) in handles.iter().enumerate() {
+            debug_assert_eq!(qt.get(*handle).unwrap().value_ref(), &Props { n: i });
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut qt = Quadtree::<u32, Props>::new(4);
+        let a = qt.insert(((1, 2), (3, 4)), Props { n: 7 }).unwrap();
+        qt.insert((9, 9), Props { n: 8 });
+
+        let exported: Value = serde_json::from_str(&qt.to_geojson()).unwrap();
+        let features = exported["features"].as_array().unwrap();
+        debug_assert_eq!(features.len(), 2);
+
+        let exported = geojson::feature_collection(qt.query((1, 2)));
+        debug_assert_eq!(
+            serde_json::from_str::<Value>(&exported).unwrap(),
+            json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "id": a,
+                    "geometry": {
+                        "type": "Polygon",
+                        "coordinates": [[[1, 2], [4, 2], [4, 6], [1, 6], [1, 2]]],
+                    },
+                    "properties": {"n": 7},
+                }],
+            })
+        );
+
+        let mut copy = Quadtree::<u32, Props>::new(4);
+        let handles = copy.insert_geojson(&exported).unwrap();
+        debug_assert_eq!(
+            copy.get(handles[0]).unwrap().area(),
+            Area::new((1, 2), (3, 4)).unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let mut qt = Quadtree::<u8, Props>::new(4);
+        let load =
+            |qt: &mut Quadtree<u8, Props>, geometries| qt.insert_geojson(&collection(geometries));
+        let point = |x: f64| json!({"type": "Point", "coordinates": [x, 0]});
+
+        assert!(matches!(qt.insert_geojson("{"), Err(GeoJsonError::Json(_))));
+        assert!(matches!(
+            qt.insert_geojson(r#"{"type": "Feature"}"#),
+            Err(GeoJsonError::NotAFeatureCollection)
+        ));
+        assert!(matches!(
+            load(
+                &mut qt,
+                vec![point(1.0), json!({"type": "Point", "coordinates": []})]
+            ),
+            Err(GeoJsonError::InvalidGeometry(1))
+        ));
+        assert!(matches!(
+            load(&mut qt, vec![point(1.0), point(20.0)]),
+            Err(GeoJsonError::Insert(1, Error::OutOfBounds))
+        ));
+        assert!(matches!(
+            load(&mut qt, vec![point(-1.0)]),
+            Err(GeoJsonError::Insert(0, Error::Overflow))
+        ));
+        assert!(matches!(
+            qt.insert_geojson(
+                r#"{"type": "FeatureCollection", "features": [
+                    {"type": "Feature", "geometry": {"type": "Point", "coordinates": [0, 0]}}
+                ]}"#
+            ),
+            Err(GeoJsonError::InvalidProperties(0, _))
+        ));
+
+        // Nothing was inserted by any failed load.
+        debug_assert!(qt.is_empty());
+    }
+}
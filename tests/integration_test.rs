@@ -14,7 +14,13 @@
 
 mod util; // For unordered_elements_are.
 
-use quadtree_rs::Quadtree;
+use crate::util::unordered_elements_are;
+use quadtree_rs::{
+    area::Area,
+    entry::Entry,
+    Error,
+    Quadtree,
+};
 
 mod new {
     use super::*;
@@ -96,6 +102,58 @@ mod insert {
     }
 }
 
+mod try_insert {
+    use super::*;
+
+    #[test]
+    fn try_insert() {
+        let mut qt = Quadtree::<u32, u8>::new(2);
+        assert!(qt.try_insert(((0, 0), (2, 3)), 4).is_ok());
+        assert!(qt.try_insert((3, 3), 4).is_ok());
+        assert!(qt.try_insert(Area::new((1, 1), (1, 1)).unwrap(), 4).is_ok());
+
+        debug_assert_eq!(qt.try_insert(((0, 0), (5, 5)), 1), Err(Error::OutOfBounds));
+        debug_assert_eq!(qt.try_insert((4, 4), 1), Err(Error::OutOfBounds));
+        debug_assert_eq!(
+            qt.try_insert(((0, 0), (0, 2)), 1),
+            Err(Error::InvalidDimensions)
+        );
+        debug_assert_eq!(
+            qt.try_insert(((u32::MAX, 0), (2, 2)), 1),
+            Err(Error::Overflow)
+        );
+        debug_assert_eq!(qt.len(), 3);
+
+        // The infallible variant just drops the error.
+        assert!(qt.insert(((0, 0), (0, 2)), 1).is_none());
+    }
+
+    #[test]
+    fn try_new() {
+        assert!(Quadtree::<i8, u8>::try_new(6).is_ok());
+        debug_assert_eq!(Quadtree::<i8, u8>::try_new(7), Err(Error::Overflow));
+        debug_assert_eq!(Quadtree::<u8, u8>::try_new(9), Err(Error::Overflow));
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::try_new_with_anchor((200, 0).into(), 6),
+            Err(Error::Overflow)
+        );
+        assert!(Quadtree::<u8, u8>::try_new_with_anchor((191, 0).into(), 6).is_ok());
+    }
+
+    // An invalid region intersects nothing, so operations over it are no-ops.
+    #[test]
+    fn invalid_region() {
+        let mut qt = Quadtree::<u32, u8>::new(2);
+        assert!(qt.insert(((0, 0), (4, 4)), 4).is_some());
+
+        debug_assert!(!qt.contains(((0, 0), (0, 0))));
+        debug_assert_eq!(qt.query(((1, 1), (0, 1))).count(), 0);
+        debug_assert_eq!(qt.query_strict(((1, 1), (0, 1))).count(), 0);
+        debug_assert_eq!(qt.delete(((1, 1), (1, 0))).count(), 0);
+        debug_assert_eq!(qt.len(), 1);
+    }
+}
+
 #[test]
 fn len() {
     let mut qt = Quadtree::<u32, u32>::new(4);
@@ -273,6 +331,547 @@ mod delete {
         // And, check that queries over the previous area don't crash or return garbage indices.
         debug_assert_eq!(qt.query((0, 0)).count(), 1);
     }
+
+    // Regression test: deleting by region used to leave handles behind in the tree, which iter()
+    // would then panic on.
+    #[test]
+    fn delete_then_iter() {
+        let mut qt = Quadtree::<u32, u8>::new(2);
+        assert!(qt.insert(((0, 0), (3, 3)), 1).is_some());
+        assert!(qt.insert((3, 3), 2).is_some());
+
+        debug_assert_eq!(qt.delete((0, 0)).count(), 1);
+        debug_assert_eq!(qt.iter().count(), 1);
+        debug_assert_eq!(qt.intersecting_pairs().count(), 0);
+    }
+}
+
+mod move_to {
+    use super::*;
+
+    // Whether every node in the tree is empty. A stale handle left behind by a move would show up
+    // here once every entry has been deleted from the store.
+    fn no_handles_in_tree<V: std::fmt::Debug>(qt: &Quadtree<u32, V>) -> bool {
+        format!("{:?}", qt)
+            .split(":: [")
+            .skip(1)
+            .all(|s| s.starts_with(']'))
+    }
+
+    #[test]
+    fn move_keeps_handle() {
+        let mut qt = Quadtree::<u32, u8>::new(4);
+        let handle = qt.insert(((0, 0), (3, 3)), 7).unwrap();
+        let other = qt.insert(((8, 8), (1, 1)), 9).unwrap();
+
+        debug_assert_eq!(
+            qt.move_to(handle, ((6, 6), (4, 4))),
+            Ok(Area::new((0, 0), (3, 3)).unwrap())
+        );
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert_eq!(qt.query(((0, 0), (6, 6))).count(), 0);
+        debug_assert!(unordered_elements_are(
+            qt.query((8, 8)).map(|e| e.handle()),
+            vec![handle, other]
+        ));
+        debug_assert_eq!(
+            qt.get(handle).unwrap().area(),
+            Area::new((6, 6), (4, 4)).unwrap()
+        );
+
+        qt.delete_by_handle(handle);
+        qt.delete_by_handle(other);
+        debug_assert!(no_handles_in_tree(&qt));
+    }
+
+    #[test]
+    fn move_unsuccessful() {
+        let mut qt = Quadtree::<u32, u8>::new(2);
+        let handle = qt.insert((1, 1), 7).unwrap();
+
+        // Too large.
+        debug_assert_eq!(
+            qt.move_to(handle, ((1, 1), (4, 4))),
+            Err(Error::OutOfBounds)
+        );
+        // Unknown handle.
+        debug_assert_eq!(
+            qt.move_to(handle + 1, (2, 2)),
+            Err(Error::UnknownHandle(handle + 1))
+        );
+        // Overflows.
+        debug_assert_eq!(qt.translate(handle, u32::MAX, 0), Err(Error::Overflow));
+
+        debug_assert_eq!(qt.get(handle).unwrap().area(), (1, 1).into());
+        debug_assert_eq!(qt.query((1, 1)).count(), 1);
+    }
+
+    #[test]
+    fn translate() {
+        let mut qt = Quadtree::<u32, u8>::new(3);
+        let handle = qt.insert(((1, 1), (2, 2)), 7).unwrap();
+
+        // Stays within the same nodes.
+        qt.translate(handle, 0, 0).unwrap();
+        // Crosses into other nodes.
+        qt.translate(handle, 3, 1).unwrap();
+
+        debug_assert_eq!(
+            qt.get(handle).unwrap().area(),
+            Area::new((4, 2), (2, 2)).unwrap()
+        );
+        debug_assert_eq!(qt.query((1, 1)).count(), 0);
+        debug_assert_eq!(qt.query((5, 3)).next().unwrap().handle(), handle);
+    }
+
+    // Shuffle many regions around and compare queries against a brute-force search.
+    #[test]
+    fn many_moves() {
+        let mut qt = Quadtree::<u32, u32>::new(5);
+        let mut seed: u32 = 29;
+        let mut next = |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+        let mut handles = vec![];
+        for i in 0..50 {
+            let (x, y) = (next(30), next(30));
+            let (w, h) = (1 + next(32 - x), 1 + next(32 - y));
+            handles.push(qt.insert(((x, y), (w, h)), i).unwrap());
+        }
+        for _ in 0..200 {
+            let handle = handles[next(50) as usize];
+            let (x, y) = (next(30), next(30));
+            let (w, h) = (1 + next(32 - x), 1 + next(32 - y));
+            assert!(qt.move_to(handle, ((x, y), (w, h))).is_ok());
+        }
+
+        for x in 0..32 {
+            for y in 0..32 {
+                let expected: Vec<u64> = qt
+                    .iter()
+                    .filter(|e| e.area().contains_pt((x, y)))
+                    .map(|e| e.handle())
+                    .collect();
+                debug_assert!(unordered_elements_are(
+                    qt.query((x, y)).map(|e| e.handle()),
+                    expected
+                ));
+            }
+        }
+
+        for handle in handles {
+            assert!(qt.delete_by_handle(handle).is_some());
+        }
+        debug_assert!(no_handles_in_tree(&qt));
+    }
+}
+
+mod split_off {
+    use super::*;
+    use quadtree_rs::Traversal;
+
+    fn mk_quadtree() -> (Quadtree<u32, char>, [u64; 3]) {
+        let mut qt = Quadtree::<u32, char>::new(4);
+        let a = qt.insert(((1, 1), (2, 2)), 'a').unwrap();
+        // Straddles the boundary between the two halves.
+        let b = qt.insert(((6, 4), (4, 4)), 'b').unwrap();
+        let c = qt.insert(((12, 12), (1, 1)), 'c').unwrap();
+        (qt, [a, b, c])
+    }
+
+    #[test]
+    fn split_off_overlapping() {
+        let (mut qt, [a, b, c]) = mk_quadtree();
+        let other = qt.split_off(((8, 0), (8, 16)), Traversal::Overlapping);
+
+        debug_assert_eq!(qt.len(), 1);
+        debug_assert_eq!(other.len(), 2);
+        debug_assert!(unordered_elements_are(
+            qt.iter().map(|e| e.handle()),
+            vec![a]
+        ));
+        debug_assert!(unordered_elements_are(
+            other.query(((0, 0), (16, 16))).map(|e| e.handle()),
+            vec![b, c]
+        ));
+        debug_assert_eq!(
+            other.get(b).unwrap().area(),
+            Area::new((6, 4), (4, 4)).unwrap()
+        );
+
+        // Nothing is left behind in the original's tree.
+        debug_assert_eq!(qt.query(((4, 4), (12, 12))).count(), 0);
+    }
+
+    #[test]
+    fn split_off_strict() {
+        let (mut qt, [a, b, c]) = mk_quadtree();
+        let other = qt.split_off(((8, 0), (8, 16)), Traversal::Strict);
+
+        debug_assert!(unordered_elements_are(
+            qt.iter().map(|e| e.handle()),
+            vec![a, b]
+        ));
+        debug_assert!(unordered_elements_are(
+            other.iter().map(|e| e.handle()),
+            vec![c]
+        ));
+    }
+
+    #[test]
+    fn split_off_then_insert() {
+        let (mut qt, handles) = mk_quadtree();
+        let mut other = qt.split_off(((0, 0), (16, 16)), Traversal::Overlapping);
+        debug_assert!(qt.is_empty());
+        debug_assert_eq!(other.depth(), qt.depth());
+
+        // Handles in the new quadtree don't collide with the moved ones.
+        let d = other.insert((0, 0), 'd').unwrap();
+        assert!(!handles.contains(&d));
+        debug_assert_eq!(other.len(), 4);
+    }
+}
+
+mod append {
+    use super::*;
+
+    #[test]
+    fn append_within_root() {
+        let mut qt = Quadtree::<u32, char>::new(4);
+        let a = qt.insert(((1, 1), (2, 2)), 'a').unwrap();
+        let mut other = Quadtree::<u32, char>::new(4);
+        let b = other.insert(((1, 1), (2, 2)), 'b').unwrap();
+        let c = other.insert((12, 3), 'c').unwrap();
+
+        let remapping = qt.append(&mut other).unwrap();
+        debug_assert!(other.is_empty());
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(qt.len(), 3);
+
+        // Both quadtrees started their handles at zero, so the moved values were renumbered.
+        debug_assert_eq!(a, b);
+        debug_assert_eq!(remapping.len(), 2);
+        assert!(!remapping.values().any(|h| *h == a));
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), &'a');
+        debug_assert_eq!(qt.get(remapping[&b]).unwrap().value_ref(), &'b');
+        debug_assert_eq!(qt.get(remapping[&c]).unwrap().value_ref(), &'c');
+        debug_assert!(unordered_elements_are(
+            qt.query(((0, 0), (2, 2))).map(|e| *e.value_ref()),
+            vec!['a', 'b']
+        ));
+    }
+
+    #[test]
+    fn append_grows_root() {
+        let mut qt = Quadtree::<i32, u8>::new(2);
+        let a = qt.insert(((2, 2), (2, 2)), 1).unwrap();
+        let mut other = Quadtree::<i32, u8>::new_with_anchor((-8, 0).into(), 3);
+        let b = other.insert(((-7, 5), (3, 1)), 2).unwrap();
+
+        let remapping = qt.append(&mut other).unwrap();
+
+        // The anchor moves left to cover (-7, 5), and the depth grows to span 11 units.
+        debug_assert_eq!(qt.anchor(), (-7, 0).into());
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(
+            qt.get(a).unwrap().area(),
+            Area::new((2, 2), (2, 2)).unwrap()
+        );
+        debug_assert_eq!(
+            qt.query((3, 3)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![a]
+        );
+        debug_assert_eq!(
+            qt.query((-5, 5)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![remapping[&b]]
+        );
+        debug_assert_eq!(qt.iter().count(), 2);
+    }
+
+    #[test]
+    fn append_overflow() {
+        let mut qt = Quadtree::<u8, u8>::new(7);
+        let a = qt.insert((0, 0), 1).unwrap();
+        let mut other = Quadtree::<u8, u8>::new_with_anchor((128, 0).into(), 6);
+        assert!(other.insert((190, 0), 2).is_some());
+
+        // Covering both would take a root 256 units wide.
+        debug_assert_eq!(qt.append(&mut other), Err(Error::Overflow));
+        debug_assert_eq!(qt.depth(), 7);
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), &1);
+        debug_assert_eq!(other.len(), 1);
+
+        // The span from the left edge of one to the right edge of the other doesn't fit in i8.
+        let mut qt = Quadtree::<i8, u8>::new_with_anchor((-64, -64).into(), 6);
+        qt.insert((-64, -64), 1).unwrap();
+        let mut other = Quadtree::<i8, u8>::new_with_anchor((0, 0).into(), 6);
+        other.insert((63, 63), 2).unwrap();
+        debug_assert_eq!(qt.append(&mut other), Err(Error::Overflow));
+        debug_assert_eq!(qt.len(), 1);
+        debug_assert_eq!(other.len(), 1);
+    }
+}
+
+mod rebuild {
+    use super::*;
+
+    #[test]
+    fn rebuild_keeps_handles() {
+        let mut qt = Quadtree::<i32, u8>::new(3);
+        let a = qt.insert(((1, 1), (2, 2)), 1).unwrap();
+        let b = qt.insert(((4, 0), (4, 8)), 2).unwrap();
+        let c = qt.insert((7, 7), 3).unwrap();
+
+        let evicted: Vec<Entry<i32, u8>> = qt.rebuild((-8, -8).into(), 4).unwrap().collect();
+        debug_assert!(evicted.is_empty());
+        debug_assert_eq!(qt.anchor(), (-8, -8).into());
+        debug_assert_eq!(qt.depth(), 4);
+
+        debug_assert!(unordered_elements_are(
+            qt.query(((0, 0), (8, 8))).map(|e| e.handle()),
+            vec![a, b, c]
+        ));
+        debug_assert_eq!(qt.query((-8, -8)).count(), 0);
+
+        // New handles don't collide with the old ones.
+        let d = qt.insert((-8, -8), 4).unwrap();
+        assert!(![a, b, c].contains(&d));
+    }
+
+    #[test]
+    fn rebuild_evicts() {
+        let mut qt = Quadtree::<u32, u8>::new(4);
+        let a = qt.insert(((1, 1), (2, 2)), 1).unwrap();
+        // Straddles the new root's far edge.
+        let b = qt.insert(((6, 6), (4, 4)), 2).unwrap();
+        let c = qt.insert((12, 12), 3).unwrap();
+
+        let evicted: Vec<Entry<u32, u8>> = qt.resize(3).unwrap().collect();
+        debug_assert!(unordered_elements_are(
+            evicted.iter().map(|e| e.handle()),
+            vec![b, c]
+        ));
+        debug_assert_eq!(qt.iter().map(|e| e.handle()).collect::<Vec<_>>(), vec![a]);
+        debug_assert!(qt.get(b).is_none());
+        debug_assert_eq!(qt.query(((0, 0), (8, 8))).count(), 1);
+    }
+
+    #[test]
+    fn rebuild_overflow() {
+        let mut qt = Quadtree::<u8, u8>::new(4);
+        let a = qt.insert((1, 1), 1).unwrap();
+
+        debug_assert!(qt.resize(8).is_err());
+        debug_assert!(qt.rebuild((200, 0).into(), 7).is_err());
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(qt.query((1, 1)).next().unwrap().handle(), a);
+    }
+}
+
+mod transform {
+    use super::*;
+
+    #[test]
+    fn translate_all() {
+        let mut qt = Quadtree::<i32, u8>::new(3);
+        let a = qt.insert(((1, 1), (2, 2)), 1).unwrap();
+        // Straddles the center, so it's replicated into subquadrants.
+        let b = qt.insert(((3, 3), (2, 2)), 2).unwrap();
+
+        qt.translate_all(-10, 5).unwrap();
+        debug_assert_eq!(qt.anchor(), (-10, 5).into());
+        debug_assert_eq!(
+            qt.get(a).unwrap().area(),
+            Area::new((-9, 6), (2, 2)).unwrap()
+        );
+        debug_assert_eq!(
+            qt.query((-9, 6)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![a]
+        );
+        debug_assert_eq!(
+            qt.query((-6, 9)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![b]
+        );
+        debug_assert_eq!(qt.query(((0, 0), (8, 8))).count(), 0);
+
+        // Subsequent inserts land in the shifted tree.
+        assert!(qt.insert((-3, 12), 3).is_some());
+        assert!(qt.insert((0, 0), 4).is_none());
+    }
+
+    #[test]
+    fn translate_all_overflow() {
+        let mut qt = Quadtree::<u8, u8>::new(4);
+        let a = qt.insert((1, 1), 1).unwrap();
+
+        debug_assert_eq!(qt.translate_all(240, 0), Err(Error::Overflow));
+        debug_assert_eq!(qt.anchor(), (0, 0).into());
+        debug_assert_eq!(qt.query((1, 1)).next().unwrap().handle(), a);
+    }
+
+    #[test]
+    fn scale_all() {
+        let mut qt = Quadtree::<i32, u8>::new_with_anchor((-4, -4).into(), 3);
+        let a = qt.insert(((-4, -4), (1, 1)), 1).unwrap();
+        let b = qt.insert(((0, 1), (3, 2)), 2).unwrap();
+
+        qt.scale_all(5).unwrap();
+        debug_assert_eq!(qt.anchor(), (-20, -20).into());
+        // 40 units wide needs a depth of 6.
+        debug_assert_eq!(qt.depth(), 6);
+        debug_assert_eq!(
+            qt.get(a).unwrap().area(),
+            Area::new((-20, -20), (5, 5)).unwrap()
+        );
+        debug_assert_eq!(
+            qt.get(b).unwrap().area(),
+            Area::new((0, 5), (15, 10)).unwrap()
+        );
+        debug_assert_eq!(
+            qt.query((14, 14)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![b]
+        );
+        debug_assert_eq!(qt.query((15, 14)).count(), 0);
+    }
+
+    #[test]
+    fn scale_all_invalid() {
+        let mut qt = Quadtree::<u8, u8>::new(4);
+        let a = qt.insert(((2, 2), (4, 4)), 1).unwrap();
+
+        debug_assert_eq!(qt.scale_all(0), Err(Error::InvalidDimensions));
+        debug_assert_eq!(qt.scale_all(16), Err(Error::Overflow));
+        debug_assert_eq!(qt.depth(), 4);
+        debug_assert_eq!(
+            qt.get(a).unwrap().area(),
+            Area::new((2, 2), (4, 4)).unwrap()
+        );
+    }
+}
+
+mod clone_and_hash {
+    use super::*;
+    use std::{
+        collections::hash_map::DefaultHasher,
+        hash::{
+            Hash,
+            Hasher,
+        },
+    };
+
+    fn hash_of(qt: &Quadtree<u32, String>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        qt.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn clone() {
+        let mut qt = Quadtree::<u32, String>::new(4);
+        let a = qt.insert(((2, 2), (9, 9)), "a".to_string()).unwrap();
+
+        let mut copy = qt.clone();
+        debug_assert_eq!(copy, qt);
+        debug_assert_eq!(hash_of(&copy), hash_of(&qt));
+
+        copy.modify((3, 3), |v| v.push('!'));
+        assert!(copy.insert((0, 0), "b".to_string()).is_some());
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), "a");
+        debug_assert_eq!(copy.get(a).unwrap().value_ref(), "a!");
+        debug_assert_eq!(qt.query((0, 0)).count(), 0);
+        assert_ne!(copy, qt);
+        assert_ne!(hash_of(&copy), hash_of(&qt));
+    }
+
+    #[test]
+    fn hash_ignores_insertion_order() {
+        let mut x = Quadtree::<u32, String>::new(3);
+        let mut y = Quadtree::<u32, String>::new(3);
+        for i in 0..20 {
+            let _ = x.insert((i % 8, i / 8), i.to_string());
+            let _ = y.insert((i % 8, i / 8), i.to_string());
+        }
+        debug_assert_eq!(hash_of(&x), hash_of(&y));
+    }
+}
+
+mod entry {
+    use super::*;
+    use quadtree_rs::entry::RegionEntry;
+
+    #[test]
+    fn vacant_then_occupied() {
+        let mut qt = Quadtree::<u32, String>::new(3);
+        // A value at an overlapping, but different, region doesn't occupy the entry.
+        let other = qt.insert(((0, 0), (4, 4)), "other".to_string()).unwrap();
+
+        match qt.entry(((1, 1), (2, 2))).unwrap() {
+            RegionEntry::Vacant(v) => {
+                debug_assert_eq!(v.area(), Area::new((1, 1), (2, 2)).unwrap());
+                v.insert("foo".to_string());
+            }
+            RegionEntry::Occupied(_) => panic!("Expected a vacant entry."),
+        }
+        debug_assert_eq!(qt.len(), 2);
+
+        match qt.entry(((1, 1), (2, 2))).unwrap() {
+            RegionEntry::Occupied(mut o) => {
+                debug_assert_ne!(o.handle(), other);
+                debug_assert_eq!(o.get(), "foo");
+                debug_assert_eq!(o.insert("bar".to_string()), "foo");
+            }
+            RegionEntry::Vacant(_) => panic!("Expected an occupied entry."),
+        }
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert_eq!(qt.get(other).unwrap().value_ref(), "other");
+    }
+
+    #[test]
+    fn or_insert_and_modify() {
+        let mut qt = Quadtree::<u32, u32>::new(3);
+
+        *qt.entry((2, 2)).unwrap().or_insert(5) += 1;
+        debug_assert_eq!(qt.query((2, 2)).next().unwrap().value_ref(), &6);
+
+        qt.entry((2, 2))
+            .unwrap()
+            .and_modify(|v| *v *= 2)
+            .or_insert_with(|| unreachable!());
+        debug_assert_eq!(qt.query((2, 2)).next().unwrap().value_ref(), &12);
+
+        *qt.entry((3, 3)).unwrap().or_default() += 7;
+        debug_assert_eq!(qt.query((3, 3)).next().unwrap().value_ref(), &7);
+        debug_assert_eq!(qt.len(), 2);
+    }
+
+    #[test]
+    fn first_of_many() {
+        let mut qt = Quadtree::<u32, u8>::new(3);
+        let first = qt.insert((1, 1), 1).unwrap();
+        qt.insert((1, 1), 2).unwrap();
+
+        match qt.entry((1, 1)).unwrap() {
+            RegionEntry::Occupied(o) => {
+                debug_assert_eq!(o.handle(), first);
+                debug_assert_eq!(o.remove().value_ref(), &1);
+            }
+            RegionEntry::Vacant(_) => panic!("Expected an occupied entry."),
+        }
+        debug_assert_eq!(qt.len(), 1);
+        debug_assert_eq!(qt.query((1, 1)).next().unwrap().value_ref(), &2);
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut qt = Quadtree::<u32, u8>::new(2);
+        debug_assert_eq!(qt.entry(((2, 2), (3, 3))).unwrap_err(), Error::OutOfBounds);
+        debug_assert_eq!(qt.entry((4, 4)).unwrap_err(), Error::OutOfBounds);
+        debug_assert_eq!(
+            qt.entry(((0, 0), (0, 1))).unwrap_err(),
+            Error::InvalidDimensions
+        );
+    }
 }
 
 #[test]
@@ -14,12 +14,14 @@
 
 mod util; // For unordered_elements_are.
 
-// For testing .iter(), .iter_mut(), .regions(), .values(), .values_mut().
+// For testing .iter(), .iter_mut(), .regions(), .values(), .values_mut(), .intersecting_pairs(),
+// .drain(), .drain_filter(), .retain(), .retain_in(), .delete_by().
 mod iterator_tests {
     use crate::util::unordered_elements_are;
     use quadtree_rs::{
         entry::Entry,
         Quadtree,
+        Traversal,
     };
 
     fn mk_quadtree_for_iter_tests() -> Quadtree<i32, i8> {
@@ -51,6 +53,44 @@ mod iterator_tests {
         ));
     }
 
+    #[test]
+    fn iter_mut() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        for entry in qt.iter_mut() {
+            *entry.value_mut() -= 1;
+        }
+        for entry in &mut qt {
+            *entry.value_mut() *= 2;
+        }
+
+        debug_assert!(unordered_elements_are(
+            qt.values().copied(),
+            vec![-52, 18, 78]
+        ));
+    }
+
+    #[test]
+    fn values_mut() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        qt.values_mut().for_each(|v| *v = v.signum());
+
+        debug_assert!(unordered_elements_are(qt.values().copied(), vec![-1, 1]));
+        debug_assert_eq!(qt.values_mut().count(), 3);
+    }
+
+    // modify_all() accepts a closure which captures mutable state.
+    #[test]
+    fn modify_all_fn_mut() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        let mut seen = vec![];
+        qt.modify_all(|v| seen.push(*v));
+
+        debug_assert!(unordered_elements_are(seen, vec![-25, 10, 40]));
+    }
+
     #[test]
     fn regions() {
         let qt = mk_quadtree_for_iter_tests();
@@ -104,6 +144,100 @@ mod iterator_tests {
         debug_assert_eq!(hit.area(), (30, -35).into());
     }
 
+    #[test]
+    fn drain() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        let mut drain = qt.drain();
+        debug_assert_eq!(drain.size_hint(), (3, Some(3)));
+        debug_assert!(drain.next().is_some());
+        // Dropping the iterator early still empties the tree.
+        drop(drain);
+
+        debug_assert!(qt.is_empty());
+        debug_assert_eq!(qt.iter().count(), 0);
+        debug_assert_eq!(qt.query(((-35, -35), (80, 80))).count(), 0);
+
+        // And the tree is still usable afterwards.
+        assert!(qt.insert((0, 0), 1).is_some());
+        debug_assert_eq!(qt.iter().count(), 1);
+    }
+
+    #[test]
+    fn drain_filter() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        // Only 10 is both in the region and matches the predicate. 40 is in the region, but
+        // doesn't match; -25 matches, but isn't in the region.
+        let returned_entries: Vec<Entry<i32, i8>> = qt
+            .drain_filter(((0, -35), (40, 40)), Traversal::Overlapping, |v| *v < 20)
+            .collect();
+        debug_assert_eq!(returned_entries.len(), 1);
+        debug_assert_eq!(returned_entries[0].value_ref(), &10);
+
+        debug_assert_eq!(qt.len(), 2);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&-25, &40]));
+    }
+
+    #[test]
+    fn drain_filter_strict() {
+        let mut qt = Quadtree::<u32, u8>::new(3);
+        assert!(qt.insert(((0, 0), (2, 2)), 1).is_some());
+        assert!(qt.insert(((1, 1), (3, 3)), 2).is_some());
+
+        // The second region only overlaps.
+        debug_assert_eq!(
+            qt.drain_filter(((0, 0), (3, 3)), Traversal::Strict, |_| true)
+                .count(),
+            1
+        );
+        debug_assert_eq!(qt.values().collect::<Vec<_>>(), vec![&2]);
+        debug_assert_eq!(qt.query((0, 0)).count(), 0);
+    }
+
+    #[test]
+    fn retain() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        // Keep the values for which the predicate is true.
+        qt.retain(|v| *v > 0);
+
+        debug_assert!(unordered_elements_are(qt.values(), vec![&10, &40]));
+        debug_assert_eq!(qt.query((-15, 20)).count(), 0);
+        debug_assert_eq!(qt.iter().count(), 2);
+    }
+
+    #[test]
+    fn retain_in() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        // Both -25 and 10 fail the predicate, but only 10 is in the region.
+        let mut calls = 0;
+        qt.retain_in(((-5, -10), (10, 10)), |v| {
+            calls += 1;
+            *v > 20
+        });
+
+        debug_assert_eq!(calls, 1);
+        debug_assert!(unordered_elements_are(qt.values(), vec![&-25, &40]));
+        debug_assert_eq!(qt.query((0, -5)).count(), 0);
+    }
+
+    #[test]
+    fn delete_by() {
+        let mut qt = mk_quadtree_for_iter_tests();
+
+        // Delete the values for which the predicate is true.
+        let returned_entries: Vec<Entry<i32, i8>> = qt.delete_by(|v| *v > 0).collect();
+
+        debug_assert!(unordered_elements_are(
+            returned_entries.iter().map(|e| e.value_ref()),
+            vec![&10, &40]
+        ));
+        debug_assert!(unordered_elements_are(qt.values(), vec![&-25]));
+        debug_assert_eq!(qt.query((30, -35)).count(), 0);
+    }
+
     #[test]
     fn delete_region_two() {
         let mut qt = mk_quadtree_for_iter_tests();
@@ -118,4 +252,68 @@ mod iterator_tests {
             vec![&-25, &10,]
         ));
     }
+
+    #[test]
+    fn intersecting_pairs_none() {
+        let qt = mk_quadtree_for_iter_tests();
+        debug_assert_eq!(qt.intersecting_pairs().count(), 0);
+    }
+
+    #[test]
+    fn intersecting_pairs_replicated() {
+        let mut qt = Quadtree::<u32, char>::new(3);
+        // Both of these straddle the center of the tree, so their handles are replicated across
+        // all four subquadrants.
+        let a = qt.insert(((2, 2), (4, 4)), 'a').unwrap();
+        let b = qt.insert(((3, 3), (2, 2)), 'b').unwrap();
+        // Touches 'a' only along an edge, which doesn't count.
+        let c = qt.insert(((6, 2), (2, 2)), 'c').unwrap();
+        // Held at the root, overlapping everything.
+        let d = qt.insert(((0, 0), (8, 8)), 'd').unwrap();
+
+        let pairs: Vec<(u64, u64)> = qt
+            .intersecting_pairs()
+            .map(|(x, y)| (x.handle(), y.handle()))
+            .collect();
+        debug_assert_eq!(pairs.len(), 4);
+        debug_assert!(unordered_elements_are(
+            pairs,
+            vec![(a, b), (a, d), (b, d), (c, d)]
+        ));
+    }
+
+    // Compare against a brute-force search over a deterministic scattering of regions.
+    #[test]
+    fn intersecting_pairs_brute_force() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut seed: u32 = 17;
+        let mut next = |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+        for i in 0..200 {
+            let (x, y) = (next(60), next(60));
+            let (w, h) = (1 + next(64 - x), 1 + next(64 - y));
+            let w = w.min(64 - x);
+            let h = h.min(64 - y);
+            assert!(qt.insert(((x, y), (w, h)), i).is_some());
+        }
+
+        let entries: Vec<&Entry<u32, u32>> = qt.iter().collect();
+        let mut expected = vec![];
+        for (n, a) in entries.iter().enumerate() {
+            for b in &entries[n + 1..] {
+                if a.area().intersects(b.area()) {
+                    expected.push((a.handle().min(b.handle()), a.handle().max(b.handle())));
+                }
+            }
+        }
+
+        let actual: Vec<(u64, u64)> = qt
+            .intersecting_pairs()
+            .map(|(x, y)| (x.handle(), y.handle()))
+            .collect();
+        debug_assert_eq!(actual.len(), expected.len());
+        debug_assert!(unordered_elements_are(actual, expected));
+    }
 }
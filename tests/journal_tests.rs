@@ -0,0 +1,222 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing JournaledQuadtree and .replay().
+mod journal_tests {
+    use quadtree_rs::{
+        binary::DecodeError,
+        journal::{
+            JournaledQuadtree,
+            ReplayError,
+        },
+        Quadtree,
+    };
+    use std::fs::{
+        self,
+        OpenOptions,
+    };
+
+    type Journal = JournaledQuadtree<i16, String, Vec<u8>>;
+
+    fn mk_journal() -> Journal {
+        let mut jqt =
+            JournaledQuadtree::new(Quadtree::new_with_anchor((-8, -8).into(), 4), Vec::new())
+                .unwrap();
+        jqt.insert(((-8, -8), (16, 16)), "everything".to_string())
+            .unwrap();
+        let doomed = jqt.insert((-3, 2), "doomed".to_string()).unwrap().unwrap();
+        jqt.insert(((1, 1), (2, 3)), "small".to_string()).unwrap();
+        jqt.modify((2, 2), |v| v.push('!')).unwrap();
+        jqt.delete_by_handle(doomed).unwrap();
+        jqt.insert((7, 7), "corner".to_string()).unwrap();
+        jqt
+    }
+
+    #[test]
+    fn replay_is_identical() {
+        let jqt = mk_journal();
+        debug_assert_eq!(jqt.records_since_checkpoint(), 7);
+
+        let qt = Quadtree::<i16, String>::replay(jqt.log()).unwrap();
+        debug_assert_eq!(qt.to_bytes().unwrap(), jqt.to_bytes().unwrap());
+        debug_assert_eq!(qt.get(0).unwrap().value_ref(), "everything!");
+        debug_assert_eq!(qt.get(2).unwrap().value_ref(), "small!");
+        debug_assert!(qt.get(1).is_none());
+        debug_assert_eq!(qt.get(3).unwrap().value_ref(), "corner");
+    }
+
+    #[test]
+    fn replay_keeps_handle_counter() {
+        let mut jqt = mk_journal();
+        jqt.reset().unwrap();
+        debug_assert!(jqt.is_empty());
+
+        let mut qt = Quadtree::<i16, String>::replay(jqt.log()).unwrap();
+        debug_assert!(qt.is_empty());
+        // Handles aren't reused after a reset, in the original or the replay.
+        debug_assert_eq!(
+            jqt.insert((0, 0), "new".to_string()).unwrap(),
+            qt.insert((0, 0), "new".to_string())
+        );
+    }
+
+    #[test]
+    fn failed_changes_arent_logged() {
+        let mut jqt = mk_journal();
+        let len = jqt.log().len();
+        debug_assert_eq!(jqt.insert((8, 8), "outside".to_string()).unwrap(), None);
+        debug_assert!(jqt.delete_by_handle(1).unwrap().is_none());
+        jqt.modify(((20, 20), (2, 2)), |v| v.clear()).unwrap();
+        debug_assert_eq!(jqt.log().len(), len);
+    }
+
+    #[test]
+    fn checkpoint_truncates_log() {
+        let mut jqt = mk_journal();
+        let len = jqt.log().len();
+        jqt.checkpoint().unwrap();
+        debug_assert!(jqt.log().len() < len);
+        debug_assert_eq!(jqt.records_since_checkpoint(), 0);
+
+        jqt.insert((0, 0), "after".to_string()).unwrap();
+        let qt = Quadtree::<i16, String>::replay(jqt.log()).unwrap();
+        debug_assert_eq!(qt.to_bytes().unwrap(), jqt.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn checkpoint_every() {
+        let mut jqt = JournaledQuadtree::new(Quadtree::<u8, u8>::new(3), Vec::new())
+            .unwrap()
+            .checkpoint_every(3);
+        let start = jqt.log().len();
+        for i in 0..5 {
+            jqt.insert((i, i), i).unwrap();
+            debug_assert_eq!(jqt.records_since_checkpoint(), (i as usize + 1) % 3);
+        }
+        // A modify writes one record per entry, so it can trigger a checkpoint, too.
+        jqt.modify(((0, 0), (8, 8)), |v| *v += 1).unwrap();
+        debug_assert_eq!(jqt.records_since_checkpoint(), 0);
+        debug_assert!(jqt.log().len() > start);
+
+        let qt = Quadtree::<u8, u8>::replay(jqt.log()).unwrap();
+        debug_assert_eq!(qt.to_bytes().unwrap(), jqt.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn replay_ignores_incomplete_record() {
+        let jqt = mk_journal();
+        let log = jqt.log();
+        // Every prefix of the log either fails before the checkpoint is complete, or replays
+        // the records written in full: the checkpoint, then each of the seven changes.
+        let mut states: Vec<Vec<u8>> = Vec::new();
+        for len in 0..=log.len() {
+            match Quadtree::<i16, String>::replay(&log[..len]) {
+                Ok(qt) => {
+                    let bytes = qt.to_bytes().unwrap();
+                    if states.last() != Some(&bytes) {
+                        states.push(bytes);
+                    }
+                }
+                Err(e) => {
+                    debug_assert!(states.is_empty());
+                    debug_assert!(
+                        e == ReplayError::BadMagic || e == ReplayError::MissingCheckpoint
+                    );
+                }
+            }
+        }
+        debug_assert_eq!(states.len(), 1 + 7);
+        debug_assert_eq!(states.last(), Some(&jqt.to_bytes().unwrap()));
+    }
+
+    #[test]
+    fn replay_errors() {
+        let mut jqt = JournaledQuadtree::new(Quadtree::<u8, u8>::new(2), Vec::new()).unwrap();
+        jqt.insert((0, 0), 1).unwrap();
+        let log = jqt.log().clone();
+        // The header, and the checkpoint record's kind and length.
+        let checkpoint = 6 + 5;
+
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(b"QTJL\x02\x00"),
+            Err(ReplayError::UnsupportedVersion(2))
+        );
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(b"QTJL\x01\x00"),
+            Err(ReplayError::MissingCheckpoint)
+        );
+
+        let mut bad = log.clone();
+        bad[checkpoint] = b'X';
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(&bad),
+            Err(ReplayError::Checkpoint(DecodeError::BadMagic))
+        );
+        debug_assert_eq!(
+            Quadtree::<u16, u8>::replay(&log),
+            Err(ReplayError::Checkpoint(DecodeError::CoordinateMismatch))
+        );
+
+        // The insert record's kind.
+        let record = log.len() - (5 + 8 + 4 + 1);
+        let mut bad = log.clone();
+        bad[record] = 9;
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(&bad),
+            Err(ReplayError::InvalidRecord(1))
+        );
+        // The insert record's handle.
+        let mut bad = log.clone();
+        bad[record + 5] = 7;
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(&bad),
+            Err(ReplayError::HandleMismatch(1))
+        );
+        // The insert record's x, which is now outside the quadtree.
+        let mut bad = log;
+        bad[record + 5 + 8] = 200;
+        debug_assert_eq!(
+            Quadtree::<u8, u8>::replay(&bad),
+            Err(ReplayError::InvalidRecord(1))
+        );
+    }
+
+    #[test]
+    fn file_log() {
+        let path = std::env::temp_dir().join(format!("quadtree_journal_{}", std::process::id()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+
+        let mut jqt = JournaledQuadtree::new(Quadtree::<u32, String>::new(4), file).unwrap();
+        let a = jqt
+            .insert(((1, 1), (3, 3)), "a".to_string())
+            .unwrap()
+            .unwrap();
+        jqt.insert((9, 9), "b".to_string()).unwrap();
+        jqt.checkpoint().unwrap();
+        jqt.modify((2, 2), |v| v.push('!')).unwrap();
+        let expected = jqt.to_bytes().unwrap();
+        drop(jqt);
+
+        let qt = Quadtree::<u32, String>::replay(&fs::read(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+        debug_assert_eq!(qt.to_bytes().unwrap(), expected);
+        debug_assert_eq!(qt.get(a).unwrap().value_ref(), "a!");
+    }
+}
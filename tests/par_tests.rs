@@ -0,0 +1,110 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "rayon")]
+
+// For testing .par_iter(), .par_query(), .par_modify_all(), and .par_insert_all().
+mod par_tests {
+    use quadtree_rs::{
+        area::Area,
+        Quadtree,
+    };
+    use rayon::prelude::*;
+
+    fn sorted(mut handles: Vec<u64>) -> Vec<u64> {
+        handles.sort_unstable();
+        handles
+    }
+
+    // Pseudo-random regions which fit in a quadtree of depth 6.
+    fn regions(n: usize) -> Vec<Area<u16>> {
+        let mut seed: u32 = 11;
+        let mut rand = |m: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % m
+        };
+        (0..n)
+            .map(|_| {
+                let (x, y) = (rand(64), rand(64));
+                // Mostly small regions, with a few large ones.
+                let max = if rand(10) == 0 { 64 } else { 4 };
+                let (w, h) = (1 + rand(max.min(64 - x)), 1 + rand(max.min(64 - y)));
+                Area::new((x as u16, y as u16), (w as u16, h as u16)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn par_insert_all_matches_insert() {
+        let regions = regions(2_000);
+        let mut seq = Quadtree::<u16, u32>::new(6);
+        let mut par = Quadtree::<u16, u32>::new(6);
+        seq.insert((1, 1), 0);
+        par.insert((1, 1), 0);
+
+        let mut entries: Vec<(Area<u16>, u32)> =
+            regions.iter().copied().zip(1..).collect::<Vec<_>>();
+        // An invalid region gets no handle.
+        entries.insert(5, (Area::new((60, 60), (8, 8)).unwrap(), 9_999));
+
+        let expected: Vec<Option<u64>> = entries
+            .iter()
+            .map(|(region, val)| seq.insert(*region, *val))
+            .collect();
+        debug_assert_eq!(par.par_insert_all(entries), expected);
+        debug_assert_eq!(expected[5], None);
+
+        // The trees are laid out identically, too.
+        debug_assert_eq!(par.len(), seq.len());
+        debug_assert_eq!(
+            par.to_bytes_with_tree().unwrap(),
+            seq.to_bytes_with_tree().unwrap()
+        );
+    }
+
+    #[test]
+    fn par_query_matches_query() {
+        let mut qt = Quadtree::<u16, usize>::new(6);
+        qt.par_insert_all(regions(1_000).into_iter().zip(0..));
+
+        for query in regions(200) {
+            debug_assert_eq!(
+                sorted(qt.par_query(query).map(|e| e.handle()).collect()),
+                sorted(qt.query(query).map(|e| e.handle()).collect())
+            );
+            debug_assert_eq!(
+                sorted(qt.par_query_strict(query).map(|e| e.handle()).collect()),
+                sorted(qt.query_strict(query).map(|e| e.handle()).collect())
+            );
+        }
+        debug_assert_eq!(qt.par_query(((0, 0), (0, 0))).count(), 0);
+    }
+
+    #[test]
+    fn par_iter_and_modify_all() {
+        let mut qt = Quadtree::<u16, usize>::new(6);
+        qt.par_insert_all(regions(500).into_iter().zip(0..));
+
+        debug_assert_eq!(
+            sorted(qt.par_iter().map(|e| e.handle()).collect()),
+            sorted(qt.iter().map(|e| e.handle()).collect())
+        );
+
+        qt.par_modify_all(|v| *v *= 3);
+        debug_assert_eq!(
+            qt.par_iter().map(|e| *e.value_ref()).sum::<usize>(),
+            3 * (0..500).sum::<usize>()
+        );
+    }
+}
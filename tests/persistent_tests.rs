@@ -0,0 +1,158 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod util; // For unordered_elements_are.
+
+// For testing PersistentQuadtree and its snapshots.
+mod persistent_tests {
+    use crate::util::unordered_elements_are;
+    use quadtree_rs::{
+        area::Area,
+        Error,
+        PersistentQuadtree,
+        Quadtree,
+    };
+
+    #[test]
+    fn snapshots_are_isolated() {
+        let mut qt = PersistentQuadtree::<u32, u8>::new(4);
+        let a = qt.insert(((0, 0), (3, 3)), 1).unwrap();
+        let b = qt.insert((9, 9), 2).unwrap();
+
+        let snapshot = qt.snapshot();
+        qt.modify(((0, 0), (4, 4)), |v| *v += 10);
+        let deleted = qt.delete_by_handle(b).unwrap();
+        debug_assert_eq!(deleted.value_ref(), &2);
+        let c = qt.insert((9, 9), 3).unwrap();
+
+        debug_assert!(unordered_elements_are(
+            qt.iter().map(|e| (e.handle(), *e.value_ref())),
+            vec![(a, 11), (c, 3)]
+        ));
+        debug_assert!(unordered_elements_are(
+            snapshot.iter().map(|e| (e.handle(), *e.value_ref())),
+            vec![(a, 1), (b, 2)]
+        ));
+        debug_assert_eq!(
+            qt.query((9, 9)).map(|e| e.handle()).collect::<Vec<_>>(),
+            vec![c]
+        );
+        debug_assert_eq!(
+            snapshot
+                .query((9, 9))
+                .map(|e| e.handle())
+                .collect::<Vec<_>>(),
+            vec![b]
+        );
+    }
+
+    #[test]
+    fn snapshot_of_snapshot() {
+        let mut qt = PersistentQuadtree::<u32, u8>::new(3);
+        let mut snapshots = vec![];
+        for i in 0..8 {
+            snapshots.push(qt.snapshot());
+            assert!(qt.insert((i, i), i as u8).is_some());
+        }
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            debug_assert_eq!(snapshot.len(), i);
+            debug_assert_eq!(snapshot.query(((0, 0), (8, 8))).count(), i);
+        }
+    }
+
+    #[test]
+    fn query_strict() {
+        let mut qt = PersistentQuadtree::<u32, char>::new(3);
+        assert!(qt.insert(((0, 0), (2, 2)), 'a').is_some());
+        assert!(qt.insert(((1, 1), (3, 3)), 'b').is_some());
+        assert!(qt.insert(((0, 0), (9, 9)), 'c').is_none());
+
+        debug_assert!(unordered_elements_are(
+            qt.query(((0, 0), (3, 3))).map(|e| *e.value_ref()),
+            vec!['a', 'b']
+        ));
+        debug_assert_eq!(
+            qt.query_strict(((0, 0), (3, 3)))
+                .map(|e| *e.value_ref())
+                .collect::<Vec<_>>(),
+            vec!['a']
+        );
+        debug_assert_eq!(qt.delete(((3, 3), (1, 1))).count(), 1);
+        debug_assert_eq!(qt.len(), 1);
+    }
+
+    // Compare against a Quadtree over enough handles to need several levels of the handle table.
+    #[test]
+    fn matches_quadtree() {
+        let mut qt = Quadtree::<u32, u32>::new(6);
+        let mut seed: u32 = 29;
+        let mut next = |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+        for i in 0..600 {
+            let (x, y) = (next(60), next(60));
+            let (w, h) = (1 + next(64 - x), 1 + next(64 - y));
+            assert!(qt
+                .insert(((x, y), (w.min(64 - x), h.min(64 - y))), i)
+                .is_some());
+        }
+        qt.delete(((10, 10), (5, 5)));
+
+        let mut persistent = PersistentQuadtree::from(qt.clone());
+        debug_assert_eq!(persistent.len(), qt.len());
+        for region in [((0, 0), (64, 64)), ((3, 40), (7, 2)), ((31, 31), (2, 2))] {
+            debug_assert!(unordered_elements_are(
+                persistent
+                    .query(region)
+                    .map(|e| (e.handle(), *e.value_ref())),
+                qt.query(region)
+                    .map(|e| (e.handle(), *e.value_ref()))
+                    .collect::<Vec<_>>()
+            ));
+        }
+
+        // Inserting after the conversion doesn't reuse any handle.
+        let h = persistent.insert((0, 0), 600).unwrap();
+        assert!(qt.get(h).is_none());
+        assert!(qt.insert((0, 0), 600).is_some());
+
+        debug_assert_eq!(Quadtree::from(persistent), qt);
+    }
+
+    #[test]
+    fn invalid_region() {
+        let mut qt = PersistentQuadtree::<u32, u8>::new(2);
+        debug_assert_eq!(qt.insert(((0, 0), (0, 1)), 1), None);
+        assert!(qt.insert(Area::new((0, 0), (4, 4)).unwrap(), 1).is_some());
+        debug_assert_eq!(qt.query(((0, 0), (0, 1))).count(), 0);
+        debug_assert!(qt.get(17).is_none());
+        debug_assert!(qt.get_mut(1 << 40).is_none());
+        debug_assert!(qt.delete_by_handle(3).is_none());
+        debug_assert_eq!(qt.len(), 1);
+    }
+
+    #[test]
+    fn try_new() {
+        debug_assert_eq!(PersistentQuadtree::<u8, u8>::try_new(7).unwrap().depth(), 7);
+        debug_assert_eq!(
+            PersistentQuadtree::<u8, u8>::try_new(8).err(),
+            Some(Error::Overflow)
+        );
+        debug_assert_eq!(
+            PersistentQuadtree::<u8, u8>::try_new_with_anchor((200, 0).into(), 7).err(),
+            Some(Error::Overflow)
+        );
+    }
+}
@@ -14,7 +14,7 @@
 
 mod util; // For unordered_elements_are.
 
-// For testing .query(), .modify().
+// For testing .query(), .query_mut(), .modify().
 mod query_tests {
     use crate::util::unordered_elements_are;
     use quadtree_rs::Quadtree;
@@ -309,4 +309,62 @@ mod query_tests {
             debug_assert_eq!(entry.value_ref(), &0);
         }
     }
+
+    #[test]
+    fn query_mut() {
+        let mut qt = Quadtree::<u32, u8>::new(4);
+        assert!(qt.insert(((0, 0), (2, 2)), 10).is_some());
+        assert!(qt.insert(((1, 1), (4, 4)), 20).is_some());
+        assert!(qt.insert((9, 9), 30).is_some());
+
+        let mut hits = 0;
+        for entry in qt.query_mut(((1, 1), (1, 1))) {
+            *entry.value_mut() += 1;
+            hits += 1;
+        }
+        debug_assert_eq!(hits, 2);
+
+        debug_assert!(unordered_elements_are(
+            qt.values().copied(),
+            vec![11, 21, 30]
+        ));
+
+        // Only the second region is wholly contained by this one.
+        for entry in qt.query_strict_mut(((1, 1), (5, 5))) {
+            *entry.value_mut() = 0;
+        }
+        debug_assert!(unordered_elements_are(
+            qt.values().copied(),
+            vec![11, 0, 30]
+        ));
+
+        // The entries are handed out once each, so they can all be held at once.
+        let mut all: Vec<_> = qt.query_mut(((0, 0), (16, 16))).collect();
+        debug_assert_eq!(all.len(), 3);
+        for entry in all.iter_mut() {
+            *entry.value_mut() += 1;
+        }
+        debug_assert!(unordered_elements_are(
+            qt.values().copied(),
+            vec![12, 1, 31]
+        ));
+
+        debug_assert_eq!(qt.query_mut(((14, 14), (2, 2))).next(), None);
+    }
+
+    // modify() and modify_strict() accept closures which capture mutable state.
+    #[test]
+    fn modify_fn_mut() {
+        let mut qt = Quadtree::<u32, u8>::new(3);
+        assert!(qt.insert(((0, 0), (2, 2)), 1).is_some());
+        assert!(qt.insert(((1, 1), (3, 3)), 2).is_some());
+
+        let mut seen = vec![];
+        qt.modify((1, 1), |v| seen.push(*v));
+        debug_assert!(unordered_elements_are(seen, vec![1, 2]));
+
+        let mut count = 0;
+        qt.modify_strict(((0, 0), (3, 3)), |_| count += 1);
+        debug_assert_eq!(count, 1);
+    }
 }
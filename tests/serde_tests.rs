@@ -0,0 +1,102 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg(feature = "serde")]
+
+// For testing the serde_entries representation.
+mod serde_tests {
+    use quadtree_rs::{
+        serde_entries,
+        Quadtree,
+    };
+    use serde_json::json;
+
+    fn to_json(qt: &Quadtree<i32, String>) -> serde_json::Value {
+        serde_entries::serialize(qt, serde_json::value::Serializer).unwrap()
+    }
+
+    fn from_json(value: serde_json::Value) -> Result<Quadtree<i32, String>, serde_json::Error> {
+        serde_entries::deserialize(value)
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut qt = Quadtree::<i32, String>::new_with_anchor((-4, -4).into(), 3);
+        let a = qt.insert(((-4, -4), (8, 8)), "a".to_string()).unwrap();
+        let b = qt.insert(((-1, -1), (2, 2)), "b".to_string()).unwrap();
+        let c = qt.insert((3, 3), "c".to_string()).unwrap();
+        qt.delete_by_handle(b);
+
+        let value = to_json(&qt);
+        debug_assert_eq!(
+            value,
+            json!({
+                "anchor": {"x": -4, "y": -4},
+                "depth": 3,
+                "handle_counter": 3,
+                "entries": [
+                    {"handle": a, "anchor": {"x": -4, "y": -4}, "dimensions": [8, 8], "value": "a"},
+                    {"handle": c, "anchor": {"x": 3, "y": 3}, "dimensions": [1, 1], "value": "c"},
+                ],
+            })
+        );
+
+        let mut copy = from_json(value).unwrap();
+        debug_assert_eq!(copy.len(), 2);
+        debug_assert_eq!(copy.get(c).unwrap().value_ref(), "c");
+        debug_assert_eq!(copy.query((3, 3)).count(), 2);
+        debug_assert_eq!(copy.query((0, 0)).count(), 1);
+        // The deleted handle isn't handed out again.
+        debug_assert_eq!(copy.insert((0, 0), "d".to_string()), Some(3));
+    }
+
+    #[test]
+    fn rejects_inconsistent_input() {
+        let with_entry = |handle: u64, anchor: (i32, i32), dimensions: (i32, i32)| {
+            from_json(json!({
+                "anchor": {"x": 0, "y": 0},
+                "depth": 2,
+                "handle_counter": 2,
+                "entries": [
+                    {"handle": 0, "anchor": {"x": 0, "y": 0}, "dimensions": [1, 1], "value": ""},
+                    {
+                        "handle": handle,
+                        "anchor": {"x": anchor.0, "y": anchor.1},
+                        "dimensions": [dimensions.0, dimensions.1],
+                        "value": "",
+                    },
+                ],
+            }))
+        };
+
+        assert!(with_entry(1, (3, 3), (1, 1)).is_ok());
+        // Invalid dimensions.
+        assert!(with_entry(1, (3, 3), (0, 1)).is_err());
+        // Out of bounds.
+        assert!(with_entry(1, (3, 3), (2, 1)).is_err());
+        // A repeated handle.
+        assert!(with_entry(0, (3, 3), (1, 1)).is_err());
+        // A handle which was never handed out.
+        assert!(with_entry(2, (3, 3), (1, 1)).is_err());
+
+        // A root which doesn't fit in i32.
+        assert!(from_json(json!({
+            "anchor": {"x": 0, "y": 0},
+            "depth": 40,
+            "handle_counter": 0,
+            "entries": [],
+        }))
+        .is_err());
+    }
+}
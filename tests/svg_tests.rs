@@ -0,0 +1,109 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .svg().
+mod svg_tests {
+    use quadtree_rs::Quadtree;
+
+    // The lines of the group with the given class, excluding its opening and closing tags.
+    fn group<'a>(svg: &'a str, class: &str) -> Vec<&'a str> {
+        let open = format!(r#"<g class="{}""#, class);
+        svg.lines()
+            .skip_while(|l| !l.trim_start().starts_with(&open))
+            .skip(1)
+            .take_while(|l| l.trim() != "</g>")
+            .map(str::trim)
+            .collect()
+    }
+
+    #[test]
+    fn empty_tree_is_just_the_root() {
+        let qt = Quadtree::<u8, ()>::new(2);
+        let svg = qt.svg().scale(4).render();
+
+        debug_assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16" viewBox="0 0 16 16">"#
+        ));
+        debug_assert!(svg.trim_end().ends_with("</svg>"));
+        debug_assert_eq!(
+            group(&svg, "nodes"),
+            vec![r#"<rect x="0" y="0" width="16" height="16"/>"#]
+        );
+        debug_assert!(group(&svg, "entries").is_empty());
+        debug_assert!(!svg.contains(r#"class="highlight""#));
+    }
+
+    #[test]
+    fn nodes_follow_subdivision() {
+        let mut qt = Quadtree::<u8, ()>::new(2);
+        qt.insert((0, 0), ());
+        // The root, its four subquadrants, and the four subquadrants of (0, 0)->2x2.
+        debug_assert_eq!(group(&qt.svg().render(), "nodes").len(), 9);
+    }
+
+    #[test]
+    fn entries_are_offset_from_the_anchor_and_filled_by_value() {
+        let mut qt = Quadtree::<i32, u8>::new_with_anchor((-4, -4).into(), 3);
+        let a = qt.insert(((-4, -4), (2, 1)), 1).unwrap();
+        let b = qt.insert((3, 3), 2).unwrap();
+
+        let svg = qt
+            .svg()
+            .scale(2)
+            .fill(|v| format!("#00{:02x}00", v))
+            .render();
+        debug_assert_eq!(
+            group(&svg, "entries"),
+            vec![
+                format!(
+                    r##"<rect x="0" y="0" width="4" height="2" fill="#000100"><title>{}</title></rect>"##,
+                    a
+                ),
+                format!(
+                    r##"<rect x="14" y="14" width="2" height="2" fill="#000200"><title>{}</title></rect>"##,
+                    b
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fill_is_escaped() {
+        let mut qt = Quadtree::<u8, ()>::new(1);
+        qt.insert((0, 0), ());
+        let svg = qt.svg().fill(|_| r#"a"<b>&"#.to_string()).render();
+        debug_assert!(svg.contains(r#"fill="a&quot;&lt;b&gt;&amp;""#));
+    }
+
+    #[test]
+    fn highlight_outlines_the_area_and_its_query_results() {
+        let mut qt = Quadtree::<u8, ()>::new(3);
+        qt.insert(((0, 0), (2, 2)), ());
+        qt.insert(((3, 3), (2, 2)), ());
+        qt.insert((7, 7), ());
+
+        let svg = qt.svg().scale(1).highlight(((1, 1), (3, 3))).render();
+        debug_assert_eq!(
+            group(&svg, "results"),
+            vec![
+                r#"<rect x="0" y="0" width="2" height="2"/>"#,
+                r#"<rect x="3" y="3" width="2" height="2"/>"#,
+            ]
+        );
+        debug_assert_eq!(
+            group(&svg, "highlight"),
+            vec![r#"<rect x="1" y="1" width="3" height="3"/>"#]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing .text().boxes() and .text().heat().
+mod text_tests {
+    use quadtree_rs::Quadtree;
+
+    #[test]
+    fn empty_tree_is_a_single_box() {
+        let qt = Quadtree::<u8, ()>::new(1);
+        debug_assert_eq!(
+            qt.text().boxes(),
+            "\
++---+---+
+|       |
++       +
+|       |
++---+---+
+"
+        );
+        debug_assert_eq!(qt.text().heat(), "┌──┐\n│  │\n│  │\n└──┘\n");
+    }
+
+    #[test]
+    fn boxes_follow_subdivision_from_the_anchor() {
+        let mut qt = Quadtree::<i8, ()>::new_with_anchor((-2, -2).into(), 2);
+        // Subdivides (0, 0)->2x2, the bottom-right subquadrant.
+        qt.insert((1, 1), ());
+        debug_assert_eq!(
+            qt.text().boxes(),
+            "\
++---+---+---+---+
+|       |       |
++       +       +
+|       |       |
++---+---+---+---+
+|       |   |   |
++       +---+---+
+|       |   |   |
++---+---+---+---+
+"
+        );
+    }
+
+    #[test]
+    fn boxes_at_scale_hide_small_nodes() {
+        let mut qt = Quadtree::<u8, ()>::new(2);
+        qt.insert((0, 0), ());
+        debug_assert_eq!(
+            qt.text().scale(2).boxes(),
+            "\
++---+---+
+|   |   |
++---+---+
+|   |   |
++---+---+
+"
+        );
+        // Rounded up to a power of two, and clipped to the width of the quadtree.
+        debug_assert_eq!(qt.text().scale(3).boxes(), qt.text().scale(4).boxes());
+        debug_assert_eq!(qt.text().scale(100).boxes(), "+---+\n|   |\n+---+\n");
+        debug_assert_eq!(qt.text().scale(0).boxes(), qt.text().boxes());
+    }
+
+    #[test]
+    fn heat_saturates_at_four() {
+        let mut qt = Quadtree::<u8, ()>::new(3);
+        for i in 0..5 {
+            qt.insert(((0, 0), (i + 1, 1)), ());
+        }
+        debug_assert_eq!(qt.text().heat().lines().nth(1), Some("│██▓▒░   │"));
+    }
+
+    #[test]
+    fn large_trees_are_capped_by_default() {
+        let mut qt = Quadtree::<u16, ()>::new(10);
+        qt.insert(((0, 0), (16, 16)), ());
+        let heat = qt.text().heat();
+        debug_assert_eq!(heat.lines().count(), 64 + 2);
+        debug_assert_eq!(heat.lines().nth(1).unwrap().chars().count(), 64 + 2);
+        debug_assert!(heat.lines().nth(1).unwrap().starts_with("│░ "));
+        debug_assert_eq!(qt.text().boxes(), qt.text().scale(16).boxes());
+        // A scale overrides the cap.
+        debug_assert_eq!(qt.text().scale(1).heat().lines().count(), 1024 + 2);
+    }
+
+    #[test]
+    fn heat_at_scale_counts_entries_overlapping_each_cell() {
+        let mut qt = Quadtree::<u8, ()>::new(3);
+        qt.insert((0, 0), ());
+        qt.insert((1, 1), ());
+        // Overlaps all four cells.
+        qt.insert(((3, 3), (2, 2)), ());
+        debug_assert_eq!(
+            qt.text().scale(4).heat(),
+            "\
+┌──┐
+│▓░│
+│░░│
+└──┘
+"
+        );
+    }
+}
@@ -1,12 +1,7 @@
-use num::{
-    cast::FromPrimitive,
-    PrimInt,
-};
-use quadtree_rs::area::AreaBuilder;
+use num::PrimInt;
 use std::{
     collections::HashSet,
     default::Default,
-    fmt::Debug,
     hash::Hash,
     iter::FromIterator,
 };
@@ -31,38 +26,7 @@ where
 #[allow(dead_code)]
 pub fn print_quadtree<U, V>(qt: &quadtree_rs::Quadtree<U, V>)
 where
-    U: PrimInt + Default + FromPrimitive + Debug,
-    V: Debug,
+    U: PrimInt + Default,
 {
-    print!("┌");
-    for _i in 0..qt.width() {
-        print!("─");
-    }
-    println!("┐");
-    for i in 0..qt.width() {
-        print!("│");
-        for j in 0..qt.height() {
-            match qt
-                .query(
-                    AreaBuilder::default()
-                        .anchor((U::from_usize(i).unwrap(), U::from_usize(j).unwrap()))
-                        .build()
-                        .unwrap(),
-                )
-                .count()
-            {
-                0 => print!(" "),
-                1 => print!("░"),
-                2 => print!("▒"),
-                3 => print!("▓"),
-                _ => print!("█"),
-            }
-        }
-        println!("|");
-    }
-    print!("└");
-    for _i in 0..qt.width() {
-        print!("─");
-    }
-    println!("┘");
+    print!("{}", qt.text().heat());
 }
@@ -0,0 +1,190 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing WatchedQuadtree and its events.
+mod watch_tests {
+    use quadtree_rs::{
+        area::Area,
+        watch::{
+            EventKind,
+            WatchedQuadtree,
+        },
+        Error,
+        Quadtree,
+    };
+
+    fn area(anchor: (i32, i32), dimensions: (i32, i32)) -> Area<i32> {
+        Area::new(anchor, dimensions).unwrap()
+    }
+
+    fn drain(qt: &mut WatchedQuadtree<i32, u8>) -> Vec<(u64, u64, EventKind<i32>)> {
+        qt.drain_events()
+            .map(|e| (e.watch(), e.handle(), e.kind()))
+            .collect()
+    }
+
+    fn mk_watched() -> (WatchedQuadtree<i32, u8>, u64, u64) {
+        let mut qt = WatchedQuadtree::new(Quadtree::new_with_anchor((-8, -8).into(), 4));
+        let west = qt.watch(((-8, -8), (8, 16))).unwrap();
+        let centre = qt.watch(((-2, -2), (4, 4))).unwrap();
+        (qt, west, centre)
+    }
+
+    #[test]
+    fn insert_delete_and_modify() {
+        let (mut qt, west, centre) = mk_watched();
+        let a = qt.insert((-5, 0), 1).unwrap();
+        let b = qt.insert(((-1, -1), (2, 2)), 2).unwrap();
+        let c = qt.insert((6, 6), 3).unwrap();
+        let (a_area, b_area) = (area((-5, 0), (1, 1)), area((-1, -1), (2, 2)));
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![
+                (west, a, EventKind::Inserted(a_area)),
+                (west, b, EventKind::Inserted(b_area)),
+                (centre, b, EventKind::Inserted(b_area)),
+            ]
+        );
+
+        qt.modify(((-8, -8), (16, 16)), |v| *v += 10);
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![
+                (west, a, EventKind::Modified(a_area)),
+                (west, b, EventKind::Modified(b_area)),
+                (centre, b, EventKind::Modified(b_area)),
+            ]
+        );
+        debug_assert_eq!(qt.get(c).unwrap().value_ref(), &13);
+
+        debug_assert_eq!(qt.delete_by_handle(b).unwrap().value_ref(), &12);
+        debug_assert!(qt.delete_by_handle(c).is_some());
+        debug_assert!(qt.delete_by_handle(c).is_none());
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![
+                (west, b, EventKind::Removed(b_area)),
+                (centre, b, EventKind::Removed(b_area)),
+            ]
+        );
+        debug_assert!(qt.events().is_empty());
+    }
+
+    #[test]
+    fn moves() {
+        let (mut qt, west, centre) = mk_watched();
+        let a = qt.insert((5, 5), 1).unwrap();
+        debug_assert!(qt.events().is_empty());
+
+        let (east, middle, corner) = (
+            area((5, 5), (1, 1)),
+            area((0, 0), (1, 1)),
+            area((-8, -8), (1, 1)),
+        );
+        debug_assert_eq!(qt.move_to(a, middle), Ok(east));
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![(
+                centre,
+                a,
+                EventKind::Entered {
+                    from: east,
+                    to: middle
+                }
+            )]
+        );
+
+        // Into the west, while staying in the centre.
+        debug_assert_eq!(qt.translate(a, -1, -1), Ok(middle));
+        let nudged = area((-1, -1), (1, 1));
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![
+                (
+                    west,
+                    a,
+                    EventKind::Entered {
+                        from: middle,
+                        to: nudged
+                    }
+                ),
+                (
+                    centre,
+                    a,
+                    EventKind::Moved {
+                        from: middle,
+                        to: nudged
+                    }
+                ),
+            ]
+        );
+
+        debug_assert_eq!(qt.move_to(a, corner), Ok(nudged));
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![
+                (
+                    west,
+                    a,
+                    EventKind::Moved {
+                        from: nudged,
+                        to: corner
+                    }
+                ),
+                (
+                    centre,
+                    a,
+                    EventKind::Left {
+                        from: nudged,
+                        to: corner
+                    }
+                ),
+            ]
+        );
+
+        // Failed moves aren't reported.
+        debug_assert_eq!(qt.translate(a, -1, 0), Err(Error::OutOfBounds));
+        debug_assert_eq!(qt.move_to(a + 1, corner), Err(Error::UnknownHandle(a + 1)));
+        debug_assert!(qt.events().is_empty());
+    }
+
+    #[test]
+    fn unwatch_and_reset() {
+        let (mut qt, west, centre) = mk_watched();
+        debug_assert_eq!(qt.watched(centre), Some(area((-2, -2), (4, 4))));
+        debug_assert_eq!(qt.watch(((0, 0), (9, 9))), None);
+
+        let a = qt.insert((0, 0), 1).unwrap();
+        let b = qt.insert((-3, 3), 2).unwrap();
+        debug_assert_eq!(qt.unwatch(centre), Some(area((-2, -2), (4, 4))));
+        debug_assert_eq!(qt.unwatch(centre), None);
+        debug_assert_eq!(qt.watched(centre), None);
+        // Events queued before the unwatch are kept.
+        debug_assert_eq!(qt.events().len(), 2);
+        qt.drain_events();
+
+        qt.move_to(a, (1, 1)).unwrap();
+        debug_assert!(qt.events().is_empty());
+
+        qt.reset();
+        debug_assert!(qt.is_empty());
+        debug_assert_eq!(
+            drain(&mut qt),
+            vec![(west, b, EventKind::Removed(area((-3, 3), (1, 1))))]
+        );
+
+        let qt = qt.into_inner();
+        debug_assert!(qt.is_empty());
+    }
+}
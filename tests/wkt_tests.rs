@@ -0,0 +1,172 @@
+// Copyright 2019 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// For testing WKT formatting and parsing of Points, Areas, and Quadtrees.
+mod wkt_tests {
+    use quadtree_rs::{
+        area::Area,
+        point::Point,
+        wkt::{
+            self,
+            ParseWktError,
+        },
+        Error,
+        Quadtree,
+    };
+
+    #[test]
+    fn point_round_trip() {
+        for &(x, y) in &[(0, 0), (-3, 7), (i32::MIN, i32::MAX)] {
+            let p = Point { x, y };
+            debug_assert_eq!(p.to_string().parse::<Point<i32>>(), Ok(p));
+        }
+        debug_assert_eq!(Point { x: 3u8, y: 4 }.to_string(), "POINT (3 4)");
+    }
+
+    #[test]
+    fn point_parsing_is_lenient_about_case_and_whitespace() {
+        debug_assert_eq!(
+            "  point(  3\t4 )\n".parse::<Point<u8>>(),
+            Ok(Point { x: 3, y: 4 })
+        );
+    }
+
+    #[test]
+    fn point_parsing_rejects_malformed_text() {
+        for s in &[
+            "",
+            "POINT",
+            "POINT ()",
+            "POINT (3)",
+            "POINT (3 4 5)",
+            "POINT (3, 4)",
+            "POINT (3 4",
+            "POINT (-1 4)",
+            "POINT (3.5 4)",
+            "POLYGON ((0 0, 1 0, 1 1, 0 1, 0 0))",
+        ] {
+            debug_assert_eq!(s.parse::<Point<u8>>(), Err(ParseWktError::Syntax), "{}", s);
+        }
+    }
+
+    #[test]
+    fn area_round_trip() {
+        for &(anchor, dims) in &[((0, 0), (1, 1)), ((-5, 2), (3, 9)), ((7, -7), (100, 1))] {
+            let a = Area::<i32>::new(anchor, dims).unwrap();
+            debug_assert_eq!(a.to_string().parse::<Area<i32>>(), Ok(a));
+        }
+    }
+
+    #[test]
+    fn area_parses_from_any_corner_in_either_direction() {
+        let expected = Area::<u32>::new((1, 2), (3, 4)).unwrap();
+        for s in &[
+            "POLYGON ((1 2, 4 2, 4 6, 1 6, 1 2))",
+            "POLYGON ((1 2, 1 6, 4 6, 4 2, 1 2))",
+            "POLYGON ((4 6, 4 2, 1 2, 1 6, 4 6))",
+            "polygon((4 2,1 2,1 6,4 6,4 2))",
+        ] {
+            debug_assert_eq!(s.parse::<Area<u32>>(), Ok(expected), "{}", s);
+        }
+    }
+
+    #[test]
+    fn area_parsing_rejects_non_rectangles() {
+        for s in &[
+            // Not closed.
+            "POLYGON ((1 2, 4 2, 4 6, 1 6, 1 3))",
+            // Too few or too many positions.
+            "POLYGON ((1 2, 4 2, 4 6, 1 2))",
+            "POLYGON ((1 2, 4 2, 4 4, 4 6, 1 6, 1 2))",
+            // A diagonal side.
+            "POLYGON ((1 2, 4 2, 4 6, 2 6, 1 2))",
+            // Corners visited out of order.
+            "POLYGON ((1 2, 4 6, 4 2, 1 6, 1 2))",
+            // Degenerate, back and forth along a line.
+            "POLYGON ((1 2, 4 2, 1 2, 4 2, 1 2))",
+        ] {
+            debug_assert_eq!(
+                s.parse::<Area<u32>>(),
+                Err(ParseWktError::NotARectangle),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn area_parsing_rejects_malformed_text() {
+        for s in &[
+            "POLYGON (1 2, 4 2, 4 6, 1 6, 1 2)",
+            "POLYGON ((1 2, 4 2, 4 6, 1 6, 1 2)",
+            "POLYGON ((1 2, 4 2, 4 6, 1 6, 1 x))",
+            "POINT (1 2)",
+        ] {
+            debug_assert_eq!(s.parse::<Area<u32>>(), Err(ParseWktError::Syntax), "{}", s);
+        }
+    }
+
+    #[test]
+    fn area_parsing_reports_unrepresentable_regions() {
+        // The width, 200 - -100, doesn't fit in an i8.
+        debug_assert_eq!(
+            "POLYGON ((-100 0, 100 0, 100 1, -100 1, -100 0))".parse::<Area<i8>>(),
+            Err(ParseWktError::Area(Error::Overflow))
+        );
+    }
+
+    #[test]
+    fn geometry_collection_of_a_query() {
+        let mut qt = Quadtree::<u32, ()>::new(4);
+        qt.insert(((0, 0), (4, 4)), ());
+        qt.insert(((10, 10), (2, 2)), ());
+
+        debug_assert_eq!(
+            wkt::geometry_collection(qt.query(((0, 0), (1, 1)))),
+            "GEOMETRYCOLLECTION (POLYGON ((0 0, 4 0, 4 4, 0 4, 0 0)))"
+        );
+        debug_assert_eq!(
+            wkt::geometry_collection(qt.query(((5, 5), (1, 1)))),
+            "GEOMETRYCOLLECTION EMPTY"
+        );
+    }
+
+    #[test]
+    fn to_wkt_is_in_handle_order_and_parses_back() {
+        let mut qt = Quadtree::<i16, ()>::new_with_anchor((-8, -8).into(), 4);
+        let regions = [((5, 5), (1, 2)), ((-8, -8), (16, 16)), ((-3, 1), (2, 1))];
+        for &region in &regions {
+            qt.insert(region, ());
+        }
+
+        let text = qt.to_wkt();
+        let body = text
+            .strip_prefix("GEOMETRYCOLLECTION (")
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap();
+        let parsed: Vec<Area<i16>> = body
+            .split(", POLYGON")
+            .enumerate()
+            .map(|(i, s)| match i {
+                0 => s.parse().unwrap(),
+                _ => format!("POLYGON{}", s).parse().unwrap(),
+            })
+            .collect();
+        let expected: Vec<Area<i16>> = regions
+            .iter()
+            .map(|&(anchor, dims)| Area::new(anchor, dims).unwrap())
+            .collect();
+        debug_assert_eq!(parsed, expected);
+    }
+}